@@ -0,0 +1,135 @@
+//! Conversion of audio into force feedback.
+//!
+//! The energy of the bass frequencies is tracked over time,
+//! and a short pulse is emitted whenever it rises sharply above
+//! its recent average (an onset), such as on a kick drum.
+//! Pulses go through a [`FfLimiter`] so that the motor is never overdriven.
+
+use std::time::{Duration, Instant};
+
+use spectrum_analyzer::FrequencySpectrum;
+use vcs_classic_hid::{
+    force_feedback::{
+        limiter::{FfLimiter, FfLimits, LimiterError},
+        pattern::FfSegment,
+    },
+    Device,
+};
+
+/// The range of frequencies considered to be bass, in Hz
+const BASS_RANGE: (f32, f32) = (20., 150.);
+
+/// Detector of onsets in the bass energy of an audio stream.
+#[derive(Debug, Clone)]
+pub struct BeatDetector {
+    /// running average of the bass energy
+    average: f32,
+    /// how much the energy must exceed the average to be an onset
+    sensitivity: f32,
+    /// energy below which nothing is detected
+    floor: f32,
+    /// minimum time between onsets
+    min_interval: Duration,
+    /// when the last onset happened
+    last_onset: Option<Duration>,
+}
+
+impl BeatDetector {
+    pub fn new() -> Self {
+        BeatDetector {
+            average: 0.,
+            sensitivity: 1.5,
+            floor: 0.02,
+            min_interval: Duration::from_millis(150),
+            last_onset: None,
+        }
+    }
+
+    /// Feed the detector with the energy of the bass at the time `now`.
+    ///
+    /// Returns the strength of the onset from 0 to 1,
+    /// or `None` if this is not an onset.
+    pub fn process(&mut self, energy: f32, now: Duration) -> Option<f32> {
+        let average = self.average;
+        self.average = average * 0.9 + energy * 0.1;
+
+        if energy < self.floor || energy < average * self.sensitivity {
+            return None;
+        }
+        if let Some(last) = self.last_onset {
+            if now.saturating_sub(last) < self.min_interval {
+                return None;
+            }
+        }
+        self.last_onset = Some(now);
+
+        // how far above the threshold, saturating at twice the threshold
+        let threshold = (average * self.sensitivity).max(self.floor);
+        Some((energy / threshold - 1.).clamp(0., 1.) * 0.5 + 0.5)
+    }
+}
+
+impl Default for BeatDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The mean magnitude of the bass frequencies in the spectrum.
+pub fn bass_energy(spectrum: &FrequencySpectrum) -> f32 {
+    let (min, max) = BASS_RANGE;
+    let (sum, count) = spectrum
+        .data()
+        .iter()
+        .filter(|(f, _)| (min..=max).contains(&f.val()))
+        .fold((0., 0), |(sum, count), (_, v)| (sum + v.val(), count + 1));
+    if count == 0 {
+        0.
+    } else {
+        sum / count as f32
+    }
+}
+
+/// Driver of force feedback pulses from audio spectra.
+#[derive(Debug)]
+pub struct Haptics {
+    detector: BeatDetector,
+    limiter: FfLimiter,
+    pulse: Duration,
+    start: Instant,
+}
+
+impl Haptics {
+    /// Create a haptics driver with pulses of at most the given intensity.
+    pub fn new(max_intensity: u8) -> Self {
+        Haptics {
+            detector: BeatDetector::new(),
+            limiter: FfLimiter::new(FfLimits {
+                max_intensity,
+                ..FfLimits::default()
+            }),
+            pulse: Duration::from_millis(60),
+            start: Instant::now(),
+        }
+    }
+
+    /// Process a new spectrum,
+    /// sending a pulse to the device if an onset is detected.
+    ///
+    /// Pulses rejected by the limiter are silently dropped.
+    pub fn update<D>(&mut self, spectrum: &FrequencySpectrum, device: D) -> Result<(), D::Error>
+    where
+        D: Device,
+    {
+        let now = self.start.elapsed();
+        let strength = match self.detector.process(bass_energy(spectrum), now) {
+            Some(strength) => strength,
+            None => return Ok(()),
+        };
+        let report = FfSegment::new(strength, self.pulse, Duration::from_secs(0), 1).to_report();
+        match self.limiter.send(report, now, device) {
+            Ok(_) | Err(LimiterError::Rejected(_)) => Ok(()),
+            Err(LimiterError::Device(e)) => Err(e),
+        }
+    }
+}
@@ -1,16 +1,21 @@
 //! Monitors the default output device.
 //!
-//!
+//! The spectrum of the audio is shown on the LED ring.
+//! With `--haptics`, the controller also pulses with the beat.
 
 use std::sync::{Arc, Mutex};
 
 use anyhow::{self, Context};
 use clap::Parser;
 use spectrum_analyzer::{self, FrequencyLimit, FrequencySpectrum, samples_fft_to_spectrum, windows::hann_window};
-use vcs_classic_hid::{self, Device, LedReport, process_input};
+use vcs_classic_hid::{self, Device, FfReport, LedReport, process_input};
 
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 
+mod haptics;
+
+use haptics::Haptics;
+
 #[derive(Debug, Parser)]
 struct App {
     /// The audio device to use
@@ -23,6 +28,12 @@ struct App {
     /// Use the JACK host
     #[clap(short = 'j', long = "--jack")]
     jack: bool,
+    /// Pulse the controller's force feedback with the beat
+    #[clap(long)]
+    haptics: bool,
+    /// The maximum intensity of the force feedback pulses
+    #[clap(long, default_value_t = 0xC0)]
+    max_intensity: u8,
 }
 
 fn main() -> Result<(), anyhow::Error> {
@@ -80,10 +91,15 @@ fn main() -> Result<(), anyhow::Error> {
     };
 
     let joy1 = joy.clone();
+    let mut haptics = if opt.haptics {
+        Some(Haptics::new(opt.max_intensity))
+    } else {
+        None
+    };
     let stream = match config.sample_format() {
         cpal::SampleFormat::F32 => device.build_input_stream(
             &config.into(),
-            move |data, _: &_| handle_input_data_f32(data, joy1.clone()),
+            move |data, _: &_| handle_input_data_f32(data, joy1.clone(), haptics.as_mut()),
             err_fn,
             None,
         )?,
@@ -112,12 +128,15 @@ fn main() -> Result<(), anyhow::Error> {
     drop(stream);
 
     joy.lock().unwrap().reset_leds()?;
+    if opt.haptics {
+        FfReport::new().send(&mut *joy.lock().unwrap())?;
+    }
     std::thread::sleep(std::time::Duration::from_millis(50));
 
     Ok(())
 }
 
-fn handle_input_data_f32<D>(input: &[f32], joy: Arc<Mutex<D>>)
+fn handle_input_data_f32<D>(input: &[f32], joy: Arc<Mutex<D>>, haptics: Option<&mut Haptics>)
 where
     D: Device,
 {
@@ -145,7 +164,12 @@ where
         led.set(i as u8, value);
     }
 
-    joy.lock().unwrap().write(led).ok();
+    let mut joy = joy.lock().unwrap();
+    joy.write(led).ok();
+
+    if let Some(haptics) = haptics {
+        haptics.update(&spectrum_hann_window, &mut *joy).ok();
+    }
 }
 
 fn process_spectrum<const N: usize>(spectrum_hann_window: &FrequencySpectrum) -> [u8; N] {
@@ -3,25 +3,21 @@
 //!
 use std::marker::PhantomData;
 
-use rand::{distributions::Uniform, Rng};
+use rand::{distributions::Uniform, rngs::ThreadRng, Rng};
 use vcs_classic_hid::{
     force_feedback::FfReport,
+    game::GameEvent,
     input::process_input,
-    led::{anims::Pulsate, AnimationEvent, LedAnimation, LedReport, LedSelection},
+    led::{
+        anims::{Pulsate, Still},
+        display::NumberDisplay,
+        mapping,
+        transition::{Transition, TransitionStyle},
+        AnimationEvent, LedAnimation, LedReport, LedSelection,
+    },
     Device,
 };
 
-#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
-pub enum GameEvent {
-    Running,
-    Ended,
-}
-impl Default for GameEvent {
-    fn default() -> Self {
-        GameEvent::Running
-    }
-}
-
 #[derive(Debug)]
 enum GameState {
     Idle {
@@ -36,15 +32,23 @@ enum GameState {
     },
     GameOver {
         base_ticks: u64,
-        animation: BiteAnimation,
+        animation: Transition<Still, BiteAnimation>,
     },
 }
 
+/// The cat and mouse game.
+///
+/// The positions of the cheese and the cat
+/// are chosen with the random number generator `R`.
 #[derive(Debug)]
-pub struct CatMouse<D> {
+pub struct CatMouse<D, R = ThreadRng> {
     phantom: PhantomData<D>,
+    rng: R,
     score: u16,
     state: GameState,
+    /// the last LED frame shown while playing,
+    /// so that the game over animation can blend from it
+    last_frame: LedReport,
 }
 
 impl<D> Default for CatMouse<D>
@@ -61,13 +65,33 @@ where
     D: Device,
 {
     pub fn new() -> Self {
+        CatMouse::new_with_rng(rand::thread_rng())
+    }
+}
+
+impl<D, R> CatMouse<D, R>
+where
+    D: Device,
+    R: Rng,
+{
+    /// Create a game which uses the given random number generator,
+    /// such as a seeded one for reproducible games.
+    pub fn new_with_rng(rng: R) -> Self {
         CatMouse {
             phantom: PhantomData,
+            rng,
             score: 0,
             state: GameState::Idle { base_ticks: 0 },
+            last_frame: LedReport::new(),
         }
     }
 
+    /// The current score,
+    /// which is the number of cheese pieces caught in the last game.
+    pub fn score(&self) -> u16 {
+        self.score
+    }
+
     pub fn update(&mut self, mut device: &mut D, ticks: u64) -> Result<GameEvent, D::Error> {
         match self.state {
             GameState::Idle { base_ticks } => {
@@ -137,7 +161,7 @@ where
                     device.write(FfReport::new_with_params(0xF8, 28, 26, 4))?;
 
                     // game over
-                    self.game_over(ticks);
+                    self.game_over(ticks, mouse_position);
                     return Ok(GameEvent::Running);
                 }
 
@@ -147,7 +171,7 @@ where
                     self.score += 1;
                     // new position for the cheese
                     Self::ff_munch_cheese(&mut device)?;
-                    cheese_position = spawn_cheese(mouse_position);
+                    cheese_position = spawn_cheese(&mut self.rng, mouse_position);
                     // make cat faster
                     match self.score {
                         10 => cat_speed = 2,
@@ -193,6 +217,7 @@ where
                 // cat: low intensity LED
                 led.set(position_to_led(cat_position), 0x46);
 
+                self.last_frame = led;
                 led.send(device)?;
             }
             GameState::GameOver {
@@ -205,7 +230,10 @@ where
                 }
 
                 let mut led = LedReport::new();
-                animation.update(ticks, &mut led);
+                if animation.update(ticks, &mut led) == AnimationEvent::Ended {
+                    // show the final score
+                    NumberDisplay::new(u32::from(self.score)).update(ticks, &mut led);
+                }
                 self.state = GameState::GameOver {
                     base_ticks,
                     animation,
@@ -229,8 +257,8 @@ where
 
     fn start(&mut self, roll: u16) {
         let mouse_position = roll as i16;
-        let cheese_position = spawn_cheese(mouse_position);
-        let cat_position = spawn_cat(mouse_position);
+        let cheese_position = spawn_cheese(&mut self.rng, mouse_position);
+        let cat_position = spawn_cat(&mut self.rng, mouse_position);
 
         self.state = GameState::Playing {
             mouse_position,
@@ -240,10 +268,20 @@ where
         };
     }
 
-    fn game_over(&mut self, ticks: u64) {
+    fn game_over(&mut self, ticks: u64, mouse_position: i16) {
+        // close in on the mouse
+        let mut animation = Transition::new(
+            Still::new(self.last_frame),
+            BiteAnimation::new(ticks),
+            TransitionStyle::Iris {
+                center: position_to_led(mouse_position),
+            },
+            16,
+        );
+        animation.reset(ticks);
         self.state = GameState::GameOver {
             base_ticks: ticks,
-            animation: BiteAnimation::new(ticks),
+            animation,
         };
         println!("Game Over\nScore: {}", self.score);
     }
@@ -256,10 +294,10 @@ where
 }
 
 /// Choose a position for the cheese
-fn spawn_cheese(mouse_position: i16) -> i16 {
+fn spawn_cheese(rng: &mut impl Rng, mouse_position: i16) -> i16 {
     let mut x: i16 = 0;
     for _ in 0..20 {
-        x = rand::thread_rng().sample(Uniform::new(0, 1024));
+        x = rng.sample(Uniform::new(0, 1024));
         if (x - mouse_position).abs() > 100 {
             break;
         }
@@ -268,10 +306,10 @@ fn spawn_cheese(mouse_position: i16) -> i16 {
 }
 
 /// Choose a position for the cat
-fn spawn_cat(mouse_position: i16) -> i16 {
+fn spawn_cat(rng: &mut impl Rng, mouse_position: i16) -> i16 {
     let mut x: i16 = 0;
     for _ in 0..20 {
-        x = rand::thread_rng().sample(Uniform::new(0, 1024));
+        x = rng.sample(Uniform::new(0, 1024));
         if (x - mouse_position).abs() > 400 {
             break;
         }
@@ -281,16 +319,7 @@ fn spawn_cat(mouse_position: i16) -> i16 {
 
 #[inline]
 fn position_to_led(position: i16) -> u8 {
-    let x = position;
-    debug_assert!(x >= 0 && x < 1024, "bad position input {}", position);
-    let out = ((x as u16).wrapping_mul(3) / 128) as u8;
-    debug_assert!(
-        out < 24,
-        "calculated invalid LED {} for position {}",
-        out,
-        position
-    );
-    out
+    mapping::roll_to_led(position as u16)
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -329,16 +358,14 @@ impl LedAnimation for BiteAnimation {
             dur if dur < BITE_CLOSING => {
                 // TODO
                 let amount = dur * 7 / BITE_CLOSING;
-                report.set_selection(LedSelection::span(0, amount as u8), 0x66);
-                report.set_selection(LedSelection::span(12, amount as u8), 0x66);
+                report.set_selection(bite_jaws(amount as u8), 0x66);
             }
             dur if dur < BITE_CRUSHING => {
                 report.fill(0x66);
             }
             dur if dur < BITE_OPENING => {
                 let amount = (BITE_OPENING - dur) * 7 / (BITE_OPENING - BITE_CRUSHING);
-                report.set_selection(LedSelection::span(0, amount as u8), 0x66);
-                report.set_selection(LedSelection::span(12, amount as u8), 0x66);
+                report.set_selection(bite_jaws(amount as u8), 0x66);
             }
             _ => {
                 // do nothing
@@ -351,3 +378,12 @@ impl LedAnimation for BiteAnimation {
         AnimationEvent::Running
     }
 }
+
+/// Select both jaws of the bite,
+/// `amount` LEDs deep from the bottom and top of the ring.
+fn bite_jaws(amount: u8) -> LedSelection {
+    if amount == 0 {
+        return LedSelection::NONE;
+    }
+    LedSelection::span(0, amount - 1).or(LedSelection::span(12, amount - 1))
+}
@@ -0,0 +1,10 @@
+//! A game where you're a mouse and you try to catch glowing cheese
+//! while avoiding the cat.
+//!
+//! The game is also available as a library,
+//! so that it can be played against any [`Device`](vcs_classic_hid::Device),
+//! such as a simulated one in tests.
+pub mod cat_mouse;
+
+pub use cat_mouse::CatMouse;
+pub use vcs_classic_hid::game::GameEvent;
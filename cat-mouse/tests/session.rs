@@ -0,0 +1,102 @@
+//! Full sessions of the game against the simulated device,
+//! with a seeded random number generator
+//! whose output is stable across versions of `rand` and platforms,
+//! unlike `StdRng`.
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use vcs_classic_hid::{
+    game::{self, FakeClock},
+    LedReport,
+};
+use vcs_classic_hid_cat_mouse::CatMouse;
+use vcs_classic_hid_simulator::{capture::LedCapture, golden::assert_golden, SimulatedDevice};
+
+const SEED: u64 = 0xCAD;
+
+/// The tick at which the player presses the Fuji button to leave
+const QUIT_TICK: u64 = 600;
+
+/// The outcome of a session
+struct Session {
+    /// the highest score reached
+    score: u16,
+    /// the number of force feedback effects sent
+    effects: usize,
+    frames: Vec<LedReport>,
+}
+
+/// Play a session in which the player starts a game
+/// and keeps rolling the paddle clockwise until caught by the cat.
+fn play(seed: u64) -> Session {
+    let mut device = LedCapture::new(SimulatedDevice::new());
+    let mut game = CatMouse::new_with_rng(ChaCha8Rng::seed_from_u64(seed));
+    let mut score = 0;
+    let mut roll = 0;
+
+    game::run(&mut device, FakeClock::new(), |device, ticks| {
+        let simulated = device.inner_mut();
+        match ticks {
+            10 => simulated.set_button_1(true),
+            12 => simulated.set_button_1(false),
+            QUIT_TICK => simulated.set_button_fuji(true),
+            ticks if ticks > 12 && ticks % 2 == 0 => {
+                roll = (roll + 12) % 1024;
+                simulated.set_roll(roll);
+            }
+            _ => {}
+        }
+        let event = game.update(device, ticks)?;
+        score = score.max(game.score());
+        Ok(event)
+    })
+    .unwrap();
+
+    let (device, frames) = device.into_parts();
+    Session {
+        score,
+        effects: device.ff_log().len(),
+        frames,
+    }
+}
+
+#[test]
+fn seeded_session() {
+    let session = play(SEED);
+
+    assert_eq!(session.score, 3);
+    // one rumble per cheese caught, plus the bite of the cat
+    assert_eq!(session.effects, usize::from(session.score) + 1);
+    assert_golden(
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/session.txt"),
+        &session.frames,
+    );
+}
+
+#[test]
+fn same_seed_same_game() {
+    let a = play(SEED);
+    let b = play(SEED);
+    assert_eq!(a.score, b.score);
+    assert_eq!(a.frames, b.frames);
+
+    let c = play(SEED + 1);
+    assert_ne!(a.frames, c.frames);
+}
+
+#[test]
+fn quit_from_idle() {
+    let mut device = SimulatedDevice::new();
+    let mut game = CatMouse::new_with_rng(ChaCha8Rng::seed_from_u64(SEED));
+    let mut clock = FakeClock::new();
+    game::run(&mut device, &mut clock, |device, ticks| {
+        if ticks == 5 {
+            device.set_button_fuji(true);
+        }
+        game.update(device, ticks)
+    })
+    .unwrap();
+    assert_eq!(clock.ticks(), 6);
+    assert_eq!(game.score(), 0);
+    // the idle animation was shown until then
+    assert_ne!(device.leds(), &[0; 24]);
+}
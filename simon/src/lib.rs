@@ -0,0 +1,9 @@
+//! A game of Simon Says on the classic controller.
+//!
+//! The game is also available as a library,
+//! so that it can be played against any [`Device`](vcs_classic_hid::Device),
+//! such as a simulated one in tests.
+pub mod simon;
+
+pub use simon::{Choice, Simon};
+pub use vcs_classic_hid::game::GameEvent;
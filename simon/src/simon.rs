@@ -1,17 +1,20 @@
 //! A game of Simon says on the classic controller
 use std::marker::PhantomData;
 
-use vcs_classic_hid::{Device, force_feedback::FfReport, input::{process_input, StickPosition}, led::{
+use vcs_classic_hid::{Device, force_feedback::FfReport, game::GameEvent, input::{process_input, StickPosition}, led::{
         anims::{Asr, Pulsate},
         AnimationEvent, LedAnimation, LedReport, LedSelection,
     }};
 
-use rand::Rng;
+use rand::{rngs::ThreadRng, Rng};
 
 /// A game of Simon Says for the classic controller.
+///
+/// The sequence is chosen with the random number generator `R`.
 #[derive(Debug)]
-pub struct Simon<D> {
+pub struct Simon<D, R = ThreadRng> {
     phantom: PhantomData<D>,
+    rng: R,
     sequence: Vec<Choice>,
     state: GameState,
 }
@@ -46,13 +49,6 @@ pub enum GameState {
     },
 }
 
-/// The event to communicate with the game loop.
-#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
-pub enum GameEvent {
-    Running,
-    Ended,
-}
-
 /// Enumeration of the possible choices that the user needs to guess.
 #[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
 pub enum Choice {
@@ -76,8 +72,15 @@ impl Choice {
 
 impl<D> Default for Simon<D> {
     fn default() -> Self {
+        Simon::default_with_rng(rand::thread_rng())
+    }
+}
+
+impl<D, R> Simon<D, R> {
+    fn default_with_rng(rng: R) -> Self {
         Simon {
             phantom: PhantomData,
+            rng,
             sequence: Vec::new(),
             state: GameState::Idle { base_tick: 0 },
         }
@@ -92,6 +95,35 @@ where
         println!("Simon!");
         Self::default()
     }
+}
+
+impl<D, R> Simon<D, R>
+where
+    D: Device,
+    R: Rng,
+{
+    /// Create a game which uses the given random number generator,
+    /// such as a seeded one for reproducible games.
+    pub fn new_with_rng(rng: R) -> Self {
+        println!("Simon!");
+        Self::default_with_rng(rng)
+    }
+
+    /// The sequence of choices in the current game.
+    pub fn sequence(&self) -> &[Choice] {
+        &self.sequence
+    }
+
+    /// The score of the current or last game,
+    /// which is the length of the sequence reached.
+    pub fn score(&self) -> usize {
+        self.sequence.len()
+    }
+
+    /// The current state of the game.
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
 
     pub fn reset(&mut self, ticks: u64) {
         println!("Simon!");
@@ -281,7 +313,7 @@ where
         println!("It begins! Watch carefully!");
 
         // pick the first two choices
-        self.sequence = vec![Self::choose(), Self::choose()];
+        self.sequence = vec![self.choose(), self.choose()];
 
         let mut anim = Self::anim_simon(self.sequence[0]);
         anim.reset(ticks);
@@ -289,15 +321,16 @@ where
     }
 
     fn next_level(&mut self, ticks: u64) {
-        self.sequence.push(Self::choose());
+        let choice = self.choose();
+        self.sequence.push(choice);
 
         let mut anim = Self::anim_simon(self.sequence[0]);
         anim.reset(ticks);
         self.state = GameState::Showing { anim, index: 0 };
     }
 
-    fn choose() -> Choice {
-        let c = rand::thread_rng().gen_range(0_u8..=3);
+    fn choose(&mut self) -> Choice {
+        let c = self.rng.gen_range(0_u8..=3);
         Choice::from_u8(c).unwrap()
     }
 
@@ -0,0 +1,117 @@
+//! Full sessions of the game against the simulated device,
+//! with a seeded random number generator
+//! whose output is stable across versions of `rand` and platforms,
+//! unlike `StdRng`.
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use vcs_classic_hid::{
+    game::{self, FakeClock},
+    LedReport, StickPosition,
+};
+use vcs_classic_hid_simon::{simon::GameState, Choice, Simon};
+use vcs_classic_hid_simulator::{capture::LedCapture, golden::assert_golden, SimulatedDevice};
+
+const SEED: u64 = 0x5140;
+
+/// The outcome of a session
+struct Session {
+    sequence: Vec<Choice>,
+    score: usize,
+    effects: usize,
+    frames: Vec<LedReport>,
+}
+
+fn stick(choice: Choice) -> StickPosition {
+    match choice {
+        Choice::Up => StickPosition::Up,
+        Choice::Right => StickPosition::Right,
+        Choice::Down => StickPosition::Down,
+        Choice::Left => StickPosition::Left,
+    }
+}
+
+fn opposite(choice: Choice) -> Choice {
+    match choice {
+        Choice::Up => Choice::Down,
+        Choice::Right => Choice::Left,
+        Choice::Down => Choice::Up,
+        Choice::Left => Choice::Right,
+    }
+}
+
+/// Play a session in which the player repeats every sequence correctly
+/// until it reaches the given length,
+/// then gets the last choice wrong and quits once the game is reset.
+fn play(seed: u64, length: usize) -> Session {
+    let mut device = LedCapture::new(SimulatedDevice::new());
+    let mut game = Simon::new_with_rng(ChaCha8Rng::seed_from_u64(seed));
+    let mut sequence = Vec::new();
+    let mut lost = false;
+
+    game::run(&mut device, FakeClock::new(), |device, ticks| {
+        let simulated = device.inner_mut();
+        match *game.state() {
+            GameState::Idle { .. } if lost => simulated.set_button_fuji(true),
+            GameState::Idle { .. } if ticks == 10 => simulated.set_button_1(true),
+            GameState::Preparing { .. } if simulated.is_button_1_down() => {
+                simulated.set_button_1(false)
+            }
+            GameState::Playing {
+                index,
+                pushed: None,
+            } => {
+                let mut choice = game.sequence()[index];
+                if game.score() == length && index == length - 1 {
+                    choice = opposite(choice);
+                }
+                simulated.move_stick(stick(choice) as u8);
+            }
+            GameState::Playing {
+                pushed: Some(_), ..
+            } => simulated.move_stick(StickPosition::Center as u8),
+            GameState::GameOver { .. } if !lost => {
+                lost = true;
+                sequence = game.sequence().to_vec();
+            }
+            _ => {}
+        }
+        game.update(device, ticks)
+    })
+    .unwrap();
+
+    let (device, frames) = device.into_parts();
+    Session {
+        score: sequence.len(),
+        sequence,
+        effects: device.ff_log().len(),
+        frames,
+    }
+}
+
+#[test]
+fn seeded_session() {
+    let session = play(SEED, 4);
+
+    assert_eq!(session.score, 4);
+    assert_eq!(
+        session.sequence,
+        vec![Choice::Up, Choice::Up, Choice::Up, Choice::Right]
+    );
+    // the game over rumble, then its cancellation on reset
+    assert_eq!(session.effects, 2);
+    assert_golden(
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/session.txt"),
+        &session.frames,
+    );
+}
+
+#[test]
+fn same_seed_same_game() {
+    let a = play(SEED, 6);
+    let b = play(SEED, 6);
+    assert_eq!(a.sequence, b.sequence);
+    assert_eq!(a.frames, b.frames);
+
+    let c = play(SEED + 1, 6);
+    assert_ne!(a.sequence, c.sequence);
+}
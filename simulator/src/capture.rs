@@ -0,0 +1,166 @@
+//! Headless capture of LED frames.
+//!
+//! [`LedCapture`] wraps any [`Device`],
+//! such as a [`SimulatedDevice`](crate::SimulatedDevice),
+//! and records the state of the LEDs after every LED report written.
+//! [`record`] obtains the frames of an animation without a device at all.
+//!
+//! The frames can then be exported as images with the [`export`](crate::export)
+//! module, or compared against stored ones with the [`golden`](crate::golden)
+//! module.
+//!
+//! # Example
+//!
+//! ```
+//! # use vcs_classic_hid::{Device, LedReport};
+//! # use vcs_classic_hid_simulator::{capture::LedCapture, SimulatedDevice};
+//! let mut device = LedCapture::new(SimulatedDevice::new());
+//! device.write(LedReport::filled(0x40))?;
+//! // a partial report only changes the first LEDs
+//! device.write([2, 2, 0xFF, 0x80])?;
+//! device.reset_leds()?;
+//!
+//! let frames = device.frames();
+//! assert_eq!(frames.len(), 3);
+//! assert_eq!(frames[1].fuji(), 0xFF);
+//! assert_eq!(frames[1].get(0), 0x80);
+//! assert_eq!(frames[1].get(1), 0x40);
+//! assert_eq!(frames[2], LedReport::new());
+//! // the reports were also written to the wrapped device
+//! assert_eq!(device.inner().leds()[0], 0x80);
+//! # Ok::<(), &'static str>(())
+//! ```
+use std::ops::Range;
+
+use vcs_classic_hid::{
+    led::{AnimationEvent, LedAnimation, FUJI},
+    Device, LedReport,
+};
+
+/// A device wrapper which records every LED frame written.
+///
+/// A frame is the full state of the LEDs after a write,
+/// so writes which only touch some of the LEDs
+/// are recorded with the remaining ones untouched.
+/// Reports which give LED control back to the controller
+/// are recorded as all LEDs off.
+#[derive(Debug)]
+pub struct LedCapture<D> {
+    inner: D,
+    current: LedReport,
+    frames: Vec<LedReport>,
+}
+
+impl<D> LedCapture<D> {
+    pub fn new(inner: D) -> Self {
+        LedCapture {
+            inner,
+            current: LedReport::new(),
+            frames: Vec::new(),
+        }
+    }
+
+    /// The frames recorded so far.
+    pub fn frames(&self) -> &[LedReport] {
+        &self.frames
+    }
+
+    /// Forget all frames recorded so far.
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    /// Take the frames recorded so far, leaving none behind.
+    pub fn take_frames(&mut self) -> Vec<LedReport> {
+        std::mem::take(&mut self.frames)
+    }
+
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut D {
+        &mut self.inner
+    }
+
+    /// Retrieve the wrapped device and the frames recorded.
+    pub fn into_parts(self) -> (D, Vec<LedReport>) {
+        (self.inner, self.frames)
+    }
+
+    /// Update the current frame from an LED report.
+    fn capture(&mut self, data: &[u8]) {
+        let len = match data.get(1) {
+            Some(len) => usize::from(*len),
+            None => return,
+        };
+        if len == 0 {
+            self.current = LedReport::new();
+        } else {
+            if let Some(fuji) = data.get(2) {
+                self.current.set(FUJI, *fuji);
+            }
+            for (led, value) in data.iter().skip(3).take(len - 1).take(24).enumerate() {
+                self.current.set(led as u8, *value);
+            }
+        }
+        self.frames.push(self.current);
+    }
+}
+
+impl<D> Device for LedCapture<D>
+where
+    D: Device,
+{
+    type Error = D::Error;
+
+    fn set_blocking(&mut self, blocking: bool) -> Result<(), Self::Error> {
+        self.inner.set_blocking(blocking)
+    }
+
+    fn read(&mut self, out: &mut [u8]) -> Result<usize, Self::Error> {
+        self.inner.read(out)
+    }
+
+    fn write<T>(&mut self, data: T) -> Result<usize, Self::Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        let data = data.as_ref();
+        let len = self.inner.write(data)?;
+        if data.first() == Some(&2) {
+            self.capture(data);
+        }
+        Ok(len)
+    }
+}
+
+/// Record the frames of an animation over the given range of ticks,
+/// starting from all LEDs off.
+///
+/// The animation is reset at the first tick.
+/// Recording stops early if the animation ends,
+/// the last frame being the one in which it ended.
+///
+/// ```
+/// # use vcs_classic_hid::led::anims::Comet;
+/// # use vcs_classic_hid_simulator::capture::record;
+/// let frames = record(Comet::new(), 0..24);
+/// assert_eq!(frames.len(), 24);
+/// ```
+pub fn record<A>(mut animation: A, ticks: Range<u64>) -> Vec<LedReport>
+where
+    A: LedAnimation,
+{
+    let mut report = LedReport::new();
+    let mut frames = Vec::new();
+    animation.reset(ticks.start);
+    for tick in ticks {
+        let event = animation.update(tick, &mut report);
+        frames.push(report);
+        if event == AnimationEvent::Ended {
+            break;
+        }
+    }
+    frames
+}
@@ -0,0 +1,270 @@
+//! Export of LED frames as images.
+//!
+//! Each frame is drawn as a square tile with the ring of LEDs
+//! (LED 0 at the bottom, going clockwise) and the Fuji LED in the middle.
+//! Frames can be laid out side by side in a PNG strip
+//! with [`png_strip`], or played in sequence in an animated GIF
+//! with [`gif_animation`].
+//! Both formats are encoded here without compression,
+//! which keeps the encoders small at the expense of file size.
+//!
+//! ```
+//! # use vcs_classic_hid::led::anims::Spinner;
+//! # use vcs_classic_hid_simulator::{capture::record, export};
+//! let frames = record(Spinner::new(), 0..8);
+//!
+//! let png = export::png_strip(&frames);
+//! assert_eq!(&png[1..4], b"PNG");
+//!
+//! let gif = export::gif_animation(&frames, 40);
+//! assert_eq!(&gif[..6], b"GIF89a");
+//! ```
+use std::{fs, io, path::Path};
+
+use vcs_classic_hid::{led::FUJI, LedReport};
+
+/// The width and height of the tile for each frame, in pixels
+pub const TILE_SIZE: usize = 64;
+
+const RING_RADIUS: f32 = 26.;
+const LED_RADIUS: f32 = 4.;
+const FUJI_RADIUS: f32 = 5.;
+
+/// palette index of the background
+const BACKGROUND: u8 = 0;
+
+/// The palette shared by all images:
+/// the background, then LED intensities from off to full.
+fn palette() -> Vec<[u8; 3]> {
+    let off: [u8; 3] = [0x40, 0x40, 0x40];
+    let full: [u8; 3] = [0xFF, 0x40, 0x00];
+    let mut palette = vec![[0x20, 0x20, 0x20]];
+    for i in 0..255 {
+        let t = i as f32 / 254.;
+        palette.push([0, 1, 2].map(|c| {
+            (f32::from(off[c]) + (f32::from(full[c]) - f32::from(off[c])) * t).round() as u8
+        }));
+    }
+    palette
+}
+
+/// The palette index for an LED value.
+fn led_index(value: u8) -> u8 {
+    1 + (u16::from(value) * 254 / 255) as u8
+}
+
+/// Draw a frame into a tile of palette indices,
+/// within an image of the given width.
+fn draw_tile(frame: &LedReport, pixels: &mut [u8], width: usize, x0: usize) {
+    let center = TILE_SIZE as f32 / 2.;
+    let mut leds = Vec::with_capacity(25);
+    for led in 0..24u8 {
+        let angle = f32::from(led) * std::f32::consts::TAU / 24.;
+        let x = center - RING_RADIUS * angle.sin();
+        let y = center + RING_RADIUS * angle.cos();
+        leds.push((x, y, LED_RADIUS, led_index(frame.get(led))));
+    }
+    leds.push((center, center, FUJI_RADIUS, led_index(frame.get(FUJI))));
+
+    for y in 0..TILE_SIZE {
+        for x in 0..TILE_SIZE {
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+            let index = leds
+                .iter()
+                .find(|(lx, ly, r, _)| (px - lx).hypot(py - ly) <= *r)
+                .map(|(_, _, _, index)| *index)
+                .unwrap_or(BACKGROUND);
+            pixels[y * width + x0 + x] = index;
+        }
+    }
+}
+
+/// Draw the frames side by side into palette indices,
+/// returning the width and height of the image.
+fn draw_strip(frames: &[LedReport]) -> (usize, usize, Vec<u8>) {
+    let width = TILE_SIZE * frames.len().max(1);
+    let height = TILE_SIZE;
+    let mut pixels = vec![BACKGROUND; width * height];
+    for (i, frame) in frames.iter().enumerate() {
+        draw_tile(frame, &mut pixels, width, i * TILE_SIZE);
+    }
+    (width, height, pixels)
+}
+
+/// Encode the frames side by side, from left to right, as a PNG image.
+pub fn png_strip(frames: &[LedReport]) -> Vec<u8> {
+    let (width, height, pixels) = draw_strip(frames);
+    encode_png(width, height, &palette(), &pixels)
+}
+
+/// Save the frames side by side as a PNG image.
+pub fn save_png_strip(path: impl AsRef<Path>, frames: &[LedReport]) -> io::Result<()> {
+    fs::write(path, png_strip(frames))
+}
+
+/// Encode the frames as an animated GIF image,
+/// showing each frame for the given time in milliseconds
+/// (rounded down to hundredths of a second)
+/// and repeating forever.
+pub fn gif_animation(frames: &[LedReport], frame_time_ms: u32) -> Vec<u8> {
+    let palette = palette();
+    let delay = (frame_time_ms / 10).min(u32::from(u16::MAX)) as u16;
+    let size = TILE_SIZE as u16;
+
+    let mut out = Vec::new();
+    out.extend_from_slice(b"GIF89a");
+    out.extend_from_slice(&size.to_le_bytes());
+    out.extend_from_slice(&size.to_le_bytes());
+    // global color table of 256 entries, 8 bits per channel
+    out.extend_from_slice(&[0xF7, BACKGROUND, 0]);
+    for color in &palette {
+        out.extend_from_slice(color);
+    }
+    // loop forever
+    out.extend_from_slice(&[0x21, 0xFF, 0x0B]);
+    out.extend_from_slice(b"NETSCAPE2.0");
+    out.extend_from_slice(&[0x03, 0x01, 0x00, 0x00, 0x00]);
+
+    let mut pixels = vec![BACKGROUND; TILE_SIZE * TILE_SIZE];
+    for frame in frames {
+        draw_tile(frame, &mut pixels, TILE_SIZE, 0);
+        // graphic control extension, for the frame's delay
+        out.extend_from_slice(&[0x21, 0xF9, 0x04, 0x00]);
+        out.extend_from_slice(&delay.to_le_bytes());
+        out.extend_from_slice(&[0x00, 0x00]);
+        // image descriptor, covering the whole screen
+        out.push(0x2C);
+        out.extend_from_slice(&[0, 0, 0, 0]);
+        out.extend_from_slice(&size.to_le_bytes());
+        out.extend_from_slice(&size.to_le_bytes());
+        out.push(0x00);
+        // image data
+        out.push(8);
+        let data = lzw_uncompressed(&pixels);
+        for block in data.chunks(255) {
+            out.push(block.len() as u8);
+            out.extend_from_slice(block);
+        }
+        out.push(0x00);
+    }
+    out.push(0x3B);
+    out
+}
+
+/// Save the frames as an animated GIF image.
+pub fn save_gif_animation(
+    path: impl AsRef<Path>,
+    frames: &[LedReport],
+    frame_time_ms: u32,
+) -> io::Result<()> {
+    fs::write(path, gif_animation(frames, frame_time_ms))
+}
+
+/// Encode a PNG image with 8-bit palette indices.
+fn encode_png(width: usize, height: usize, palette: &[[u8; 3]], pixels: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(b"\x89PNG\r\n\x1a\n");
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    // bit depth 8, indexed color, default compression, filter and interlace
+    header.extend_from_slice(&[8, 3, 0, 0, 0]);
+    png_chunk(&mut out, b"IHDR", &header);
+
+    let colors: Vec<u8> = palette.iter().flatten().copied().collect();
+    png_chunk(&mut out, b"PLTE", &colors);
+
+    // each row starts with its filter type, none
+    let mut raw = Vec::with_capacity((width + 1) * height);
+    for row in pixels.chunks(width) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+    png_chunk(&mut out, b"IDAT", &zlib_stored(&raw));
+    png_chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// Wrap the data in a zlib stream of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xFFFF).peekable();
+    if blocks.peek().is_none() {
+        // a single empty final block
+        out.extend_from_slice(&[1, 0x00, 0x00, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+
+    // Adler-32 checksum
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + u32::from(byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    out.extend_from_slice(&((b << 16) | a).to_be_bytes());
+    out
+}
+
+/// Encode 8-bit indices as GIF LZW codes without any actual compression:
+/// every index is emitted as a literal 9-bit code,
+/// with the table cleared before the code size would grow.
+fn lzw_uncompressed(indices: &[u8]) -> Vec<u8> {
+    const CLEAR: u16 = 256;
+    const END: u16 = 257;
+    // literals after a clear code which keep the table below 512 entries
+    const RUN: usize = 254;
+
+    let mut out = Vec::new();
+    let mut bits = 0u32;
+    let mut nbits = 0;
+    let mut emit = |code: u16, out: &mut Vec<u8>| {
+        bits |= u32::from(code) << nbits;
+        nbits += 9;
+        while nbits >= 8 {
+            out.push(bits as u8);
+            bits >>= 8;
+            nbits -= 8;
+        }
+    };
+
+    for run in indices.chunks(RUN) {
+        emit(CLEAR, &mut out);
+        for &index in run {
+            emit(u16::from(index), &mut out);
+        }
+    }
+    emit(END, &mut out);
+    if nbits > 0 {
+        out.push(bits as u8);
+    }
+    out
+}
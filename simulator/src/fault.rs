@@ -0,0 +1,56 @@
+//! Fault injection for the simulated device.
+//!
+//! Set up a [`Faults`] configuration with
+//! [`SimulatedDevice::set_faults`](crate::SimulatedDevice::set_faults)
+//! to make the device misbehave like a real one occasionally does,
+//! so that error paths in applications can be exercised.
+//! Disconnections are simulated with
+//! [`unplug`](crate::SimulatedDevice::unplug) and
+//! [`replug`](crate::SimulatedDevice::replug),
+//! and arbitrary reports can be delivered with
+//! [`inject_report`](crate::SimulatedDevice::inject_report).
+//!
+//! # Example
+//!
+//! ```
+//! # use vcs_classic_hid::{Device, LedReport};
+//! # use vcs_classic_hid_simulator::SimulatedDevice;
+//! # use vcs_classic_hid_simulator::fault::{Faults, WRITE_ERROR};
+//! let mut device = SimulatedDevice::new();
+//! device.set_faults(Faults {
+//!     fail_writes_after: Some(2),
+//!     ..Faults::default()
+//! });
+//!
+//! let report = LedReport::filled(0xFF);
+//! assert!(device.write(&report).is_ok());
+//! assert!(device.write(&report).is_ok());
+//! assert_eq!(device.write(&report), Err(WRITE_ERROR));
+//! ```
+use std::time::Duration;
+
+/// The error returned by any operation on an unplugged device
+pub const DISCONNECTED: &str = "Device disconnected";
+
+/// The error returned by an injected read failure
+pub const READ_ERROR: &str = "Injected read error";
+
+/// The error returned by an injected write failure
+pub const WRITE_ERROR: &str = "Injected write error";
+
+/// A configuration of faults to inject into the simulated device.
+///
+/// The default configuration injects no faults.
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+pub struct Faults {
+    /// Fail all reads after this many successful reads
+    pub fail_reads_after: Option<u64>,
+    /// Fail all writes after this many successful writes
+    pub fail_writes_after: Option<u64>,
+    /// Accept at most this many bytes per write,
+    /// the rest of the report is ignored
+    pub max_write_len: Option<usize>,
+    /// How long it takes for an input report to become available for reading,
+    /// in the simulated device's clock
+    pub latency: Duration,
+}
@@ -0,0 +1,153 @@
+//! Names of common evdev event types and codes,
+//! as in `linux/input-event-codes.h`.
+
+/// Synchronization events, marking the end of a group of events
+pub const EV_SYN: u16 = 0x00;
+/// Key and button events
+pub const EV_KEY: u16 = 0x01;
+/// Absolute axis events
+pub const EV_ABS: u16 = 0x03;
+
+pub const SYN_REPORT: u16 = 0x00;
+
+pub const BTN_SOUTH: u16 = 0x130;
+pub const BTN_EAST: u16 = 0x131;
+pub const BTN_NORTH: u16 = 0x133;
+pub const BTN_WEST: u16 = 0x134;
+pub const BTN_TL: u16 = 0x136;
+pub const BTN_TR: u16 = 0x137;
+pub const BTN_SELECT: u16 = 0x13a;
+pub const BTN_START: u16 = 0x13b;
+pub const BTN_MODE: u16 = 0x13c;
+pub const BTN_DPAD_UP: u16 = 0x220;
+pub const BTN_DPAD_DOWN: u16 = 0x221;
+pub const BTN_DPAD_LEFT: u16 = 0x222;
+pub const BTN_DPAD_RIGHT: u16 = 0x223;
+
+pub const ABS_X: u16 = 0x00;
+pub const ABS_Y: u16 = 0x01;
+pub const ABS_Z: u16 = 0x02;
+pub const ABS_RX: u16 = 0x03;
+pub const ABS_RY: u16 = 0x04;
+pub const ABS_RZ: u16 = 0x05;
+pub const ABS_HAT0X: u16 = 0x10;
+pub const ABS_HAT0Y: u16 = 0x11;
+
+/// Key and button codes by name
+const KEYS: &[(&str, u16)] = &[
+    ("KEY_ESC", 1),
+    ("KEY_1", 2),
+    ("KEY_2", 3),
+    ("KEY_3", 4),
+    ("KEY_4", 5),
+    ("KEY_5", 6),
+    ("KEY_6", 7),
+    ("KEY_7", 8),
+    ("KEY_8", 9),
+    ("KEY_9", 10),
+    ("KEY_0", 11),
+    ("KEY_BACKSPACE", 14),
+    ("KEY_TAB", 15),
+    ("KEY_Q", 16),
+    ("KEY_W", 17),
+    ("KEY_E", 18),
+    ("KEY_R", 19),
+    ("KEY_T", 20),
+    ("KEY_Y", 21),
+    ("KEY_U", 22),
+    ("KEY_I", 23),
+    ("KEY_O", 24),
+    ("KEY_P", 25),
+    ("KEY_ENTER", 28),
+    ("KEY_LEFTCTRL", 29),
+    ("KEY_A", 30),
+    ("KEY_S", 31),
+    ("KEY_D", 32),
+    ("KEY_F", 33),
+    ("KEY_G", 34),
+    ("KEY_H", 35),
+    ("KEY_J", 36),
+    ("KEY_K", 37),
+    ("KEY_L", 38),
+    ("KEY_LEFTSHIFT", 42),
+    ("KEY_Z", 44),
+    ("KEY_X", 45),
+    ("KEY_C", 46),
+    ("KEY_V", 47),
+    ("KEY_B", 48),
+    ("KEY_N", 49),
+    ("KEY_M", 50),
+    ("KEY_RIGHTSHIFT", 54),
+    ("KEY_LEFTALT", 56),
+    ("KEY_SPACE", 57),
+    ("KEY_RIGHTCTRL", 97),
+    ("KEY_RIGHTALT", 100),
+    ("KEY_UP", 103),
+    ("KEY_LEFT", 105),
+    ("KEY_RIGHT", 106),
+    ("KEY_DOWN", 108),
+    ("BTN_SOUTH", BTN_SOUTH),
+    ("BTN_A", BTN_SOUTH),
+    ("BTN_EAST", BTN_EAST),
+    ("BTN_B", BTN_EAST),
+    ("BTN_C", 0x132),
+    ("BTN_NORTH", BTN_NORTH),
+    ("BTN_X", BTN_NORTH),
+    ("BTN_WEST", BTN_WEST),
+    ("BTN_Y", BTN_WEST),
+    ("BTN_Z", 0x135),
+    ("BTN_TL", BTN_TL),
+    ("BTN_TR", BTN_TR),
+    ("BTN_TL2", 0x138),
+    ("BTN_TR2", 0x139),
+    ("BTN_SELECT", BTN_SELECT),
+    ("BTN_START", BTN_START),
+    ("BTN_MODE", BTN_MODE),
+    ("BTN_THUMBL", 0x13d),
+    ("BTN_THUMBR", 0x13e),
+    ("BTN_DPAD_UP", BTN_DPAD_UP),
+    ("BTN_DPAD_DOWN", BTN_DPAD_DOWN),
+    ("BTN_DPAD_LEFT", BTN_DPAD_LEFT),
+    ("BTN_DPAD_RIGHT", BTN_DPAD_RIGHT),
+];
+
+/// Absolute axis codes by name
+const AXES: &[(&str, u16)] = &[
+    ("ABS_X", ABS_X),
+    ("ABS_Y", ABS_Y),
+    ("ABS_Z", ABS_Z),
+    ("ABS_RX", ABS_RX),
+    ("ABS_RY", ABS_RY),
+    ("ABS_RZ", ABS_RZ),
+    ("ABS_THROTTLE", 0x06),
+    ("ABS_RUDDER", 0x07),
+    ("ABS_WHEEL", 0x08),
+    ("ABS_GAS", 0x09),
+    ("ABS_BRAKE", 0x0a),
+    ("ABS_HAT0X", ABS_HAT0X),
+    ("ABS_HAT0Y", ABS_HAT0Y),
+];
+
+/// Look up an event type and code by name,
+/// such as `BTN_SOUTH` or `ABS_X`.
+pub fn from_name(name: &str) -> Option<(u16, u16)> {
+    let find = |table: &[(&str, u16)]| {
+        table
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, code)| *code)
+    };
+    find(KEYS)
+        .map(|code| (EV_KEY, code))
+        .or_else(|| find(AXES).map(|code| (EV_ABS, code)))
+}
+
+/// The name of a code of the given event type, if known.
+pub fn name(kind: u16, code: u16) -> Option<&'static str> {
+    let table = match kind {
+        EV_KEY => KEYS,
+        EV_ABS => AXES,
+        _ => return None,
+    };
+    table.iter().find(|(_, c)| *c == code).map(|(n, _)| *n)
+}
@@ -0,0 +1,256 @@
+//! Reading input events from Linux evdev devices.
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Read},
+    os::unix::{fs::OpenOptionsExt, io::AsRawFd},
+    path::{Path, PathBuf},
+};
+
+use vcs_classic_hid::State;
+
+use super::{codes, Bridge, InputEvent, Mapping};
+
+/// The environment variable with the path to the evdev device to use,
+/// or `auto` to use the first gamepad found
+const GAMEPAD_VAR: &str = "VCS_SIMULATOR_GAMEPAD";
+/// The environment variable with the path to a mapping file
+const MAPPING_VAR: &str = "VCS_SIMULATOR_GAMEPAD_MAPPING";
+
+/// Build an ioctl request number for reading `size` bytes,
+/// as `_IOC(_IOC_READ, 'E', nr, size)`.
+const fn ioc_read(nr: u32, size: usize) -> u32 {
+    (2 << 30) | ((size as u32) << 16) | ((b'E' as u32) << 8) | nr
+}
+
+/// `struct input_absinfo`
+#[repr(C)]
+#[derive(Debug, Default)]
+struct AbsInfo {
+    value: i32,
+    minimum: i32,
+    maximum: i32,
+    fuzz: i32,
+    flat: i32,
+    resolution: i32,
+}
+
+/// A gamepad, keyboard or other evdev input device,
+/// mapped onto the classic controller.
+#[derive(Debug)]
+pub struct Gamepad {
+    file: File,
+    path: PathBuf,
+    bridge: Bridge,
+}
+
+impl Gamepad {
+    /// Open an evdev device, such as `/dev/input/event5`.
+    ///
+    /// The ranges of the axes in the mapping are read from the device.
+    /// Reading input devices usually requires being in the `input` group.
+    pub fn open(path: impl AsRef<Path>, mapping: Mapping) -> io::Result<Self> {
+        let path = path.as_ref();
+        let file = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(path)?;
+        let mut gamepad = Gamepad {
+            file,
+            path: path.to_path_buf(),
+            bridge: Bridge::new(mapping),
+        };
+
+        let axes: Vec<u16> = gamepad
+            .bridge
+            .mapping()
+            .bindings()
+            .iter()
+            .filter(|b| b.kind == codes::EV_ABS)
+            .map(|b| b.code)
+            .collect();
+        for code in axes {
+            if let Ok((min, max)) = gamepad.axis_range(code) {
+                gamepad.bridge.set_axis_range(code, min, max);
+            }
+        }
+        Ok(gamepad)
+    }
+
+    /// Find the first evdev device with gamepad buttons.
+    pub fn find() -> io::Result<Option<PathBuf>> {
+        let mut paths: Vec<PathBuf> = fs::read_dir("/dev/input")?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .map(|name| name.starts_with("event"))
+                    .unwrap_or(false)
+            })
+            .collect();
+        // event2 before event10
+        paths.sort_by_key(|path| (path.as_os_str().len(), path.clone()));
+
+        for path in paths {
+            let file = match File::open(&path) {
+                Ok(file) => file,
+                Err(_) => continue,
+            };
+            if has_key(&file, codes::BTN_SOUTH).unwrap_or(false) {
+                return Ok(Some(path));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Open the gamepad configured through the environment, if any.
+    ///
+    /// `VCS_SIMULATOR_GAMEPAD` is the path to the device,
+    /// or `auto` for the first gamepad found,
+    /// and `VCS_SIMULATOR_GAMEPAD_MAPPING` is an optional mapping file.
+    /// Problems are reported to standard error.
+    pub fn from_env() -> Option<Self> {
+        let path = std::env::var_os(GAMEPAD_VAR)?;
+        let path = if path == "auto" {
+            match Gamepad::find() {
+                Ok(Some(path)) => path,
+                Ok(None) => {
+                    eprintln!("No gamepad found");
+                    return None;
+                }
+                Err(e) => {
+                    eprintln!("Could not look for gamepads: {}", e);
+                    return None;
+                }
+            }
+        } else {
+            PathBuf::from(path)
+        };
+
+        let mapping = match std::env::var_os(MAPPING_VAR) {
+            None => Mapping::default(),
+            Some(file) => {
+                let mapping = fs::read_to_string(&file)
+                    .map_err(|e| e.to_string())
+                    .and_then(|text| text.parse().map_err(|e: super::ParseError| e.to_string()));
+                match mapping {
+                    Ok(mapping) => mapping,
+                    Err(e) => {
+                        eprintln!(
+                            "Invalid gamepad mapping {}: {}",
+                            Path::new(&file).display(),
+                            e
+                        );
+                        return None;
+                    }
+                }
+            }
+        };
+
+        match Gamepad::open(&path, mapping) {
+            Ok(gamepad) => Some(gamepad),
+            Err(e) => {
+                eprintln!("Could not open gamepad {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The name of the device.
+    pub fn name(&self) -> io::Result<String> {
+        let mut buf = [0u8; 256];
+        let request = ioc_read(0x06, buf.len());
+        let len = unsafe { libc::ioctl(self.file.as_raw_fd(), request as _, buf.as_mut_ptr()) };
+        if len < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let name = buf.split(|b| *b == 0).next().unwrap_or_default();
+        Ok(String::from_utf8_lossy(name).into_owned())
+    }
+
+    /// The range of an absolute axis, as reported by the device.
+    pub fn axis_range(&self, code: u16) -> io::Result<(i32, i32)> {
+        let mut info = AbsInfo::default();
+        let request = ioc_read(0x40 + u32::from(code), std::mem::size_of::<AbsInfo>());
+        let result = unsafe {
+            libc::ioctl(
+                self.file.as_raw_fd(),
+                request as _,
+                &mut info as *mut AbsInfo,
+            )
+        };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok((info.minimum, info.maximum))
+    }
+
+    pub fn bridge(&self) -> &Bridge {
+        &self.bridge
+    }
+
+    pub fn bridge_mut(&mut self) -> &mut Bridge {
+        &mut self.bridge
+    }
+
+    /// Read all pending events without blocking.
+    pub fn read_events(&mut self) -> io::Result<Vec<InputEvent>> {
+        let mut events = Vec::new();
+        let mut buf = [0; InputEvent::RAW_SIZE * 32];
+        loop {
+            match self.file.read(&mut buf) {
+                Ok(0) => break,
+                Ok(len) => events.extend(
+                    buf[..len]
+                        .chunks_exact(InputEvent::RAW_SIZE)
+                        .filter_map(InputEvent::from_raw),
+                ),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(events)
+    }
+
+    /// Process all pending events and combine the gamepad's input
+    /// with input from another source for one frame,
+    /// see [`Bridge::merge`].
+    pub fn update(&mut self, other: State) -> io::Result<State> {
+        for event in self.read_events()? {
+            self.bridge.handle(&event);
+        }
+        Ok(self.bridge.merge(other))
+    }
+}
+
+/// Whether the device has the given key or button.
+fn has_key(file: &File, code: u16) -> io::Result<bool> {
+    // enough bits for all key codes (KEY_MAX is 0x2ff)
+    let mut bits = [0u8; 0x300 / 8];
+    let request = ioc_read(0x20 + u32::from(codes::EV_KEY), bits.len());
+    let result = unsafe { libc::ioctl(file.as_raw_fd(), request as _, bits.as_mut_ptr()) };
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let code = usize::from(code);
+    Ok(bits[code / 8] & (1 << (code % 8)) != 0)
+}
+
+/// Combine the input of an optional gamepad with other input,
+/// dropping the gamepad if it can no longer be read.
+#[cfg(any(feature = "gui", feature = "tui"))]
+pub(crate) fn merge_input(gamepad: &mut Option<Gamepad>, state: State) -> State {
+    let result = match gamepad {
+        Some(gamepad) => gamepad.update(state),
+        None => return state,
+    };
+    result.unwrap_or_else(|e| {
+        eprintln!("Gamepad disconnected: {}", e);
+        *gamepad = None;
+        state
+    })
+}
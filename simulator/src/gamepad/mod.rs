@@ -0,0 +1,809 @@
+//! Control of the simulated device with a standard gamepad or keyboard.
+//!
+//! A [`Bridge`] turns Linux evdev input events into controller input,
+//! according to a [`Mapping`] from keys, buttons and axes
+//! to the controls of the classic controller.
+//! The events may come from a real device
+//! (with the `gamepad` feature, on Linux, see `Gamepad`)
+//! or from a [`Recording`], replayed with a [`Replay`].
+//!
+//! # Mapping format
+//!
+//! Mappings can be written in a small text format,
+//! with one binding per line in the form `<input> <control> [min max]`.
+//! Empty lines and anything after a `#` are ignored.
+//!
+//! Inputs are evdev code names such as `BTN_SOUTH`, `KEY_SPACE` or `ABS_X`,
+//! or numeric codes written as `key:<code>` or `abs:<code>`.
+//! The controls are:
+//!
+//! | Control                   | Bound to      | Meaning                                     |
+//! |---------------------------|---------------|---------------------------------------------|
+//! | `1`, `2`, `back`, `menu`, `fuji` | key or axis | a button, pressed past half of the axis |
+//! | `up`, `right`, `down`, `left` | key       | a direction of the stick                    |
+//! | `stick-x`, `stick-y`      | axis          | the stick, tilted past the dead zone        |
+//! | `roll`                    | axis          | the paddle, from the axis' position         |
+//! | `roll-left`, `roll-right` | key or axis   | roll the paddle continuously                |
+//!
+//! The optional range of an axis overrides the one reported by the device,
+//! and an inverted range inverts the axis.
+//! The lines `roll-speed <value>` and `dead-zone <value>` set
+//! how much the paddle rolls per frame at full speed (16 by default)
+//! and how far the stick must be tilted, from 0 to 1 (0.5 by default).
+//!
+//! ```
+//! # use vcs_classic_hid::StickPosition;
+//! # use vcs_classic_hid_simulator::gamepad::{codes, Bridge, InputEvent, Mapping};
+//! let mapping: Mapping = "
+//!     KEY_SPACE 1
+//!     KEY_UP up
+//!     ABS_X stick-x -32768 32767
+//!     ABS_RZ roll 0 255  # a trigger as the paddle
+//! ".parse()?;
+//!
+//! let mut bridge = Bridge::new(mapping);
+//! bridge.handle(&InputEvent::new(codes::EV_KEY, 57, 1));
+//! bridge.handle(&InputEvent::new(codes::EV_KEY, 103, 1));
+//! bridge.handle(&InputEvent::new(codes::EV_ABS, codes::ABS_X, 30000));
+//! bridge.handle(&InputEvent::new(codes::EV_ABS, codes::ABS_RZ, 255));
+//!
+//! let state = bridge.state();
+//! assert!(state.button_1);
+//! assert_eq!(state.stick_position, StickPosition::UpRight);
+//! assert_eq!(state.roll, 1023);
+//! # Ok::<(), vcs_classic_hid_simulator::gamepad::ParseError>(())
+//! ```
+//!
+//! # Recordings
+//!
+//! Input can be recorded with `evtest`,
+//! whose output is read by [`Recording::parse_evtest`],
+//! including the ranges of the axes.
+//! The raw events read from `/dev/input/event*`
+//! are read by [`Recording::from_raw`].
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    time::Duration,
+};
+
+use vcs_classic_hid::{led::mapping::ROLL_RANGE, State, StickPosition};
+
+pub use crate::ParseError;
+use crate::{script::Button, SimulatedDevice};
+
+pub mod codes;
+#[cfg(all(feature = "gamepad", target_os = "linux"))]
+mod evdev;
+
+#[cfg(all(
+    feature = "gamepad",
+    target_os = "linux",
+    any(feature = "gui", feature = "tui")
+))]
+pub(crate) use evdev::merge_input;
+#[cfg(all(feature = "gamepad", target_os = "linux"))]
+pub use evdev::Gamepad;
+
+use codes::{EV_ABS, EV_KEY, EV_SYN, SYN_REPORT};
+
+/// A single evdev input event.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub struct InputEvent {
+    /// The time stamp of the event
+    pub time: Duration,
+    /// The event type, such as [`EV_KEY`](codes::EV_KEY)
+    pub kind: u16,
+    pub code: u16,
+    pub value: i32,
+}
+
+impl InputEvent {
+    /// The size of an event as read from an evdev device
+    /// (a `struct input_event`)
+    pub const RAW_SIZE: usize = 2 * std::mem::size_of::<usize>() + 8;
+
+    /// Create an event without a time stamp.
+    pub fn new(kind: u16, code: u16, value: i32) -> Self {
+        InputEvent {
+            time: Duration::default(),
+            kind,
+            code,
+            value,
+        }
+    }
+
+    /// Decode an event as read from an evdev device,
+    /// which must be [`RAW_SIZE`](InputEvent::RAW_SIZE) bytes long.
+    pub fn from_raw(data: &[u8]) -> Option<Self> {
+        if data.len() != Self::RAW_SIZE {
+            return None;
+        }
+        let word = std::mem::size_of::<usize>();
+        let int = |bytes: &[u8]| {
+            let mut buf = [0; 8];
+            buf[..bytes.len()].copy_from_slice(bytes);
+            u64::from_ne_bytes(buf)
+        };
+        let seconds = int(&data[..word]);
+        let micros = int(&data[word..2 * word]);
+        let rest = &data[2 * word..];
+        Some(InputEvent {
+            time: Duration::from_secs(seconds) + Duration::from_micros(micros),
+            kind: u16::from_ne_bytes([rest[0], rest[1]]),
+            code: u16::from_ne_bytes([rest[2], rest[3]]),
+            value: i32::from_ne_bytes([rest[4], rest[5], rest[6], rest[7]]),
+        })
+    }
+}
+
+/// A control of the classic controller to which an input is bound.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum Control {
+    /// A button
+    Button(Button),
+    /// A direction of the stick,
+    /// one of up, right, down or left
+    Stick(StickPosition),
+    /// The horizontal axis of the stick
+    StickX,
+    /// The vertical axis of the stick, down being positive
+    StickY,
+    /// The absolute position of the paddle
+    Roll,
+    /// Roll the paddle counterclockwise continuously
+    RollLeft,
+    /// Roll the paddle clockwise continuously
+    RollRight,
+}
+
+impl Control {
+    fn from_name(name: &str) -> Option<Self> {
+        let control = match name {
+            "up" => Control::Stick(StickPosition::Up),
+            "right" => Control::Stick(StickPosition::Right),
+            "down" => Control::Stick(StickPosition::Down),
+            "left" => Control::Stick(StickPosition::Left),
+            "stick-x" => Control::StickX,
+            "stick-y" => Control::StickY,
+            "roll" => Control::Roll,
+            "roll-left" => Control::RollLeft,
+            "roll-right" => Control::RollRight,
+            name => Control::Button(Button::from_name(name)?),
+        };
+        Some(control)
+    }
+}
+
+/// A binding of an evdev input to a control.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Binding {
+    /// The event type of the input, either `EV_KEY` or `EV_ABS`
+    pub kind: u16,
+    pub code: u16,
+    pub control: Control,
+    /// The range of the axis, if it should not be taken from the device
+    pub range: Option<(i32, i32)>,
+}
+
+/// A mapping of evdev inputs to the controls of the classic controller.
+///
+/// The default mapping follows the standard Linux gamepad layout:
+///
+/// - the D-pad and the left stick move the stick;
+/// - the south and east face buttons are buttons 1 and 2;
+/// - select, start and mode are the back, menu and Fuji buttons;
+/// - the triggers and shoulder buttons roll the paddle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mapping {
+    bindings: Vec<Binding>,
+    roll_speed: u16,
+    dead_zone: f32,
+}
+
+impl Default for Mapping {
+    fn default() -> Self {
+        use codes::*;
+        Mapping::new()
+            .bind_key(BTN_SOUTH, Control::Button(Button::One))
+            .bind_key(BTN_EAST, Control::Button(Button::Two))
+            .bind_key(BTN_SELECT, Control::Button(Button::Back))
+            .bind_key(BTN_START, Control::Button(Button::Menu))
+            .bind_key(BTN_MODE, Control::Button(Button::Fuji))
+            .bind_key(BTN_DPAD_UP, Control::Stick(StickPosition::Up))
+            .bind_key(BTN_DPAD_RIGHT, Control::Stick(StickPosition::Right))
+            .bind_key(BTN_DPAD_DOWN, Control::Stick(StickPosition::Down))
+            .bind_key(BTN_DPAD_LEFT, Control::Stick(StickPosition::Left))
+            .bind_key(BTN_TL, Control::RollLeft)
+            .bind_key(BTN_TR, Control::RollRight)
+            .bind_axis(ABS_HAT0X, Control::StickX, None)
+            .bind_axis(ABS_HAT0Y, Control::StickY, None)
+            .bind_axis(ABS_X, Control::StickX, None)
+            .bind_axis(ABS_Y, Control::StickY, None)
+            .bind_axis(ABS_Z, Control::RollLeft, None)
+            .bind_axis(ABS_RZ, Control::RollRight, None)
+    }
+}
+
+impl Mapping {
+    /// Create a mapping without any bindings.
+    pub fn new() -> Self {
+        Mapping {
+            bindings: Vec::new(),
+            roll_speed: 16,
+            dead_zone: 0.5,
+        }
+    }
+
+    /// Bind a key or button to a control.
+    pub fn bind_key(mut self, code: u16, control: Control) -> Self {
+        self.bindings.push(Binding {
+            kind: EV_KEY,
+            code,
+            control,
+            range: None,
+        });
+        self
+    }
+
+    /// Bind an absolute axis to a control,
+    /// optionally with the range of the axis.
+    pub fn bind_axis(mut self, code: u16, control: Control, range: Option<(i32, i32)>) -> Self {
+        self.bindings.push(Binding {
+            kind: EV_ABS,
+            code,
+            control,
+            range,
+        });
+        self
+    }
+
+    /// Set how much the paddle rolls per frame at full speed.
+    pub fn with_roll_speed(mut self, roll_speed: u16) -> Self {
+        self.roll_speed = roll_speed;
+        self
+    }
+
+    /// Set how far the stick must be tilted, from 0 to 1.
+    pub fn with_dead_zone(mut self, dead_zone: f32) -> Self {
+        self.dead_zone = dead_zone;
+        self
+    }
+
+    pub fn bindings(&self) -> &[Binding] {
+        &self.bindings
+    }
+}
+
+/// Parse an input name into its event type and code.
+fn input_from_name(name: &str) -> Option<(u16, u16)> {
+    if let Some(input) = codes::from_name(name) {
+        return Some(input);
+    }
+    let (kind, code) = name.split_once(':')?;
+    let kind = match kind {
+        "key" => EV_KEY,
+        "abs" => EV_ABS,
+        _ => return None,
+    };
+    let code = match code.strip_prefix("0x") {
+        Some(hex) => u16::from_str_radix(hex, 16).ok()?,
+        None => code.parse().ok()?,
+    };
+    Some((kind, code))
+}
+
+impl FromStr for Mapping {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut mapping = Mapping::new();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default();
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if words.is_empty() {
+                continue;
+            }
+            let error = |message: String| ParseError {
+                line: i + 1,
+                message,
+            };
+            let arg = |index: usize, what: &str| {
+                words
+                    .get(index)
+                    .ok_or_else(|| error(format!("missing {}", what)))
+            };
+
+            match words[0] {
+                "roll-speed" => {
+                    let word = arg(1, "roll speed")?;
+                    mapping.roll_speed = word
+                        .parse()
+                        .map_err(|_| error(format!("invalid roll speed `{}`", word)))?;
+                    continue;
+                }
+                "dead-zone" => {
+                    let word = arg(1, "dead zone")?;
+                    mapping.dead_zone = word
+                        .parse()
+                        .ok()
+                        .filter(|v| (0. ..=1.).contains(v))
+                        .ok_or_else(|| error(format!("invalid dead zone `{}`", word)))?;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let (kind, code) = input_from_name(words[0])
+                .ok_or_else(|| error(format!("unknown input `{}`", words[0])))?;
+            let name = arg(1, "control")?;
+            let control = Control::from_name(name)
+                .ok_or_else(|| error(format!("unknown control `{}`", name)))?;
+            let invalid = matches!(
+                (kind, control),
+                (EV_KEY, Control::StickX | Control::StickY | Control::Roll)
+                    | (EV_ABS, Control::Stick(_))
+            );
+            if invalid {
+                return Err(error(format!(
+                    "`{}` cannot be bound to `{}`",
+                    words[0], name
+                )));
+            }
+
+            let range = match words.get(2) {
+                None => None,
+                Some(_) if kind == EV_KEY => {
+                    return Err(error("only axes can have a range".to_string()))
+                }
+                Some(min) => {
+                    let max = arg(3, "maximum of the range")?;
+                    let value = |word: &str| {
+                        word.parse::<i32>()
+                            .map_err(|_| error(format!("invalid axis value `{}`", word)))
+                    };
+                    let range = (value(min)?, value(max)?);
+                    if range.0 == range.1 {
+                        return Err(error("empty axis range".to_string()));
+                    }
+                    Some(range)
+                }
+            };
+            if words.len() > 4 {
+                return Err(error(format!("unexpected `{}`", words[4])));
+            }
+            mapping.bindings.push(Binding {
+                kind,
+                code,
+                control,
+                range,
+            });
+        }
+        Ok(mapping)
+    }
+}
+
+/// Turns evdev input events into the input state of the classic controller.
+#[derive(Debug, Clone)]
+pub struct Bridge {
+    mapping: Mapping,
+    /// keys currently pressed
+    keys: HashSet<u16>,
+    /// the last value of each axis
+    axes: HashMap<u16, i32>,
+    /// the ranges of the axes reported by the device
+    ranges: HashMap<u16, (i32, i32)>,
+    roll: u16,
+    /// whether the paddle was moved since the last merge
+    rolled: bool,
+}
+
+impl Bridge {
+    pub fn new(mapping: Mapping) -> Self {
+        Bridge {
+            mapping,
+            keys: HashSet::new(),
+            axes: HashMap::new(),
+            ranges: HashMap::new(),
+            roll: 0,
+            rolled: false,
+        }
+    }
+
+    pub fn mapping(&self) -> &Mapping {
+        &self.mapping
+    }
+
+    /// Set the range of an axis as reported by the device.
+    ///
+    /// Ranges in the mapping take precedence.
+    /// Axes without a known range are assumed to go
+    /// from -1 to 1 if they are a D-pad (`ABS_HAT*`),
+    /// from -32768 to 32767 if bound to the stick,
+    /// or from 0 to 255 otherwise, as the triggers of most gamepads.
+    pub fn set_axis_range(&mut self, code: u16, min: i32, max: i32) {
+        if min != max {
+            self.ranges.insert(code, (min, max));
+        }
+    }
+
+    /// Process an input event.
+    pub fn handle(&mut self, event: &InputEvent) {
+        match event.kind {
+            EV_KEY if event.value == 0 => {
+                self.keys.remove(&event.code);
+            }
+            EV_KEY => {
+                self.keys.insert(event.code);
+            }
+            EV_ABS => {
+                self.axes.insert(event.code, event.value);
+                let roll = self
+                    .mapping
+                    .bindings
+                    .iter()
+                    .filter(|b| b.kind == EV_ABS && b.code == event.code)
+                    .find(|b| b.control == Control::Roll)
+                    .map(|b| self.level(b));
+                if let Some(level) = roll {
+                    self.roll = (level * f32::from(ROLL_RANGE - 1)).round() as u16;
+                    self.rolled = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// How far a bound input is pushed, from 0 to 1.
+    fn level(&self, binding: &Binding) -> f32 {
+        match binding.kind {
+            EV_KEY => self.keys.contains(&binding.code) as u8 as f32,
+            _ => {
+                let value = match self.axes.get(&binding.code) {
+                    Some(value) => *value,
+                    None => return 0.,
+                };
+                let (min, max) = binding
+                    .range
+                    .or_else(|| self.ranges.get(&binding.code).copied())
+                    .unwrap_or(match binding.control {
+                        _ if (0x10..=0x17).contains(&binding.code) => (-1, 1),
+                        Control::StickX | Control::StickY => (-32768, 32767),
+                        _ => (0, 255),
+                    });
+                // in 64 bits, as the range may span all 32 bit values
+                let (value, min, max) = (i64::from(value), i64::from(min), i64::from(max));
+                ((value - min) as f32 / (max - min) as f32).clamp(0., 1.)
+            }
+        }
+    }
+
+    /// Roll the paddle by one frame's worth
+    /// of the inputs bound to rolling continuously.
+    pub fn step(&mut self) {
+        let mut delta = 0.;
+        for binding in &self.mapping.bindings {
+            let sign = match binding.control {
+                Control::RollLeft => -1.,
+                Control::RollRight => 1.,
+                _ => continue,
+            };
+            let level = self.level(binding);
+            // ignore the noise of triggers at rest
+            if level > 0.05 {
+                delta += sign * level * f32::from(self.mapping.roll_speed);
+            }
+        }
+        let delta = delta.round() as i32;
+        if delta != 0 {
+            self.roll = (i32::from(self.roll) + delta).rem_euclid(i32::from(ROLL_RANGE)) as u16;
+            self.rolled = true;
+        }
+    }
+
+    /// The controller input state from the inputs so far.
+    pub fn state(&self) -> State {
+        let (mut x, mut y) = (0., 0.);
+        let mut state = State {
+            roll: self.roll,
+            ..State::default()
+        };
+        let (mut up, mut right, mut down, mut left) = (false, false, false, false);
+
+        for binding in &self.mapping.bindings {
+            let level = self.level(binding);
+            match binding.control {
+                Control::Button(button) => {
+                    let down = level > 0.5;
+                    match button {
+                        Button::One => state.button_1 |= down,
+                        Button::Two => state.button_2 |= down,
+                        Button::Back => state.button_back |= down,
+                        Button::Menu => state.button_menu |= down,
+                        Button::Fuji => state.button_fuji |= down,
+                    }
+                }
+                Control::Stick(direction) if level > 0.5 => match direction {
+                    StickPosition::Up => up = true,
+                    StickPosition::Right => right = true,
+                    StickPosition::Down => down = true,
+                    StickPosition::Left => left = true,
+                    _ => {}
+                },
+                // keep the axis furthest from the center
+                Control::StickX if self.axes.contains_key(&binding.code) => {
+                    let value = level * 2. - 1.;
+                    if value.abs() > f32::abs(x) {
+                        x = value;
+                    }
+                }
+                Control::StickY if self.axes.contains_key(&binding.code) => {
+                    let value = level * 2. - 1.;
+                    if value.abs() > f32::abs(y) {
+                        y = value;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let dead_zone = self.mapping.dead_zone;
+        up |= y < -dead_zone;
+        down |= y > dead_zone;
+        left |= x < -dead_zone;
+        right |= x > dead_zone;
+        state.stick_position = StickPosition::from_directions(up, right, down, left);
+        state
+    }
+
+    /// Combine the input from the bridge with input from another source,
+    /// such as the keyboard of the simulator's window,
+    /// after rolling the paddle by one frame.
+    ///
+    /// Buttons are down if down in either,
+    /// the stick is taken from the bridge unless centered,
+    /// and the paddle is taken from the bridge if it moved since the last merge.
+    pub fn merge(&mut self, other: State) -> State {
+        self.step();
+        let state = self.state();
+        let roll = if self.rolled {
+            self.rolled = false;
+            self.roll
+        } else {
+            self.roll = other.roll;
+            other.roll
+        };
+        State {
+            stick_position: if state.stick_position != StickPosition::Center {
+                state.stick_position
+            } else {
+                other.stick_position
+            },
+            button_1: state.button_1 || other.button_1,
+            button_2: state.button_2 || other.button_2,
+            button_back: state.button_back || other.button_back,
+            button_menu: state.button_menu || other.button_menu,
+            button_fuji: state.button_fuji || other.button_fuji,
+            roll,
+        }
+    }
+
+    /// Apply the input to a simulated device for one frame,
+    /// pushing a new input state if anything changed.
+    pub fn apply(&mut self, device: &mut SimulatedDevice) {
+        let current = device.state();
+        let state = self.merge(State {
+            roll: current.roll,
+            ..State::default()
+        });
+        if state != current {
+            device.push_state(state);
+        }
+    }
+}
+
+/// A sequence of recorded evdev input events.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Recording {
+    /// The ranges of the axes of the recorded device, by code
+    pub ranges: Vec<(u16, i32, i32)>,
+    pub events: Vec<InputEvent>,
+}
+
+impl Recording {
+    /// Decode the raw events as read from an evdev device,
+    /// ignoring any incomplete event at the end.
+    pub fn from_raw(data: &[u8]) -> Self {
+        Recording {
+            ranges: Vec::new(),
+            events: data
+                .chunks_exact(InputEvent::RAW_SIZE)
+                .filter_map(InputEvent::from_raw)
+                .collect(),
+        }
+    }
+
+    /// Parse the output of `evtest`.
+    ///
+    /// The ranges of the axes are taken from the description of the device,
+    /// and every line starting with `Event:` is an event.
+    /// All other lines are ignored.
+    ///
+    /// ```
+    /// # use vcs_classic_hid_simulator::gamepad::{codes, Recording};
+    /// let recording = Recording::parse_evtest("
+    /// Supported events:
+    ///   Event type 3 (EV_ABS)
+    ///     Event code 2 (ABS_Z)
+    ///       Value      0
+    ///       Min        0
+    ///       Max      255
+    /// Testing ... (interrupt to exit)
+    /// Event: time 1700000000.250000, type 1 (EV_KEY), code 304 (BTN_SOUTH), value 1
+    /// Event: time 1700000000.250000, -------------- SYN_REPORT ------------
+    /// ")?;
+    /// assert_eq!(recording.ranges, vec![(codes::ABS_Z, 0, 255)]);
+    /// assert_eq!(recording.events.len(), 2);
+    /// assert_eq!(recording.events[0].code, codes::BTN_SOUTH);
+    /// # Ok::<(), vcs_classic_hid_simulator::gamepad::ParseError>(())
+    /// ```
+    pub fn parse_evtest(text: &str) -> Result<Self, ParseError> {
+        let mut recording = Recording::default();
+        // the event type and code being described
+        let mut kind = None;
+        let mut code = None;
+        let mut min = None;
+
+        for (i, line) in text.lines().enumerate() {
+            let error = |message: &str| ParseError {
+                line: i + 1,
+                message: message.to_string(),
+            };
+            let line = line.trim();
+            let number = |word: Option<&str>| {
+                word.and_then(|w| w.parse::<i32>().ok())
+                    .ok_or_else(|| error("invalid number"))
+            };
+
+            if let Some(event) = line.strip_prefix("Event:") {
+                recording
+                    .events
+                    .push(parse_evtest_event(event).ok_or_else(|| error("invalid event"))?);
+            } else if let Some(rest) = line.strip_prefix("Event type ") {
+                kind = Some(number(rest.split_whitespace().next())? as u16);
+                code = None;
+            } else if let Some(rest) = line.strip_prefix("Event code ") {
+                code = Some(number(rest.split_whitespace().next())? as u16);
+                min = None;
+            } else if let (Some(EV_ABS), Some(code)) = (kind, code) {
+                if let Some(rest) = line.strip_prefix("Min") {
+                    min = Some(number(rest.split_whitespace().next())?);
+                } else if let Some(rest) = line.strip_prefix("Max") {
+                    let max = number(rest.split_whitespace().next())?;
+                    if let Some(min) = min {
+                        recording.ranges.push((code, min, max));
+                    }
+                }
+            }
+        }
+        Ok(recording)
+    }
+
+    /// The time between the first and last events.
+    pub fn duration(&self) -> Duration {
+        match (self.events.first(), self.events.last()) {
+            (Some(first), Some(last)) => last.time.saturating_sub(first.time),
+            _ => Duration::default(),
+        }
+    }
+}
+
+/// Parse the part of an `evtest` event line after `Event:`, such as
+/// `time 1700000000.250000, type 1 (EV_KEY), code 304 (BTN_SOUTH), value 1`.
+fn parse_evtest_event(line: &str) -> Option<InputEvent> {
+    let mut parts = line.split(',').map(str::trim);
+    let time = parts.next()?.strip_prefix("time ")?;
+    let (seconds, micros) = time.split_once('.')?;
+    let time =
+        Duration::from_secs(seconds.parse().ok()?) + Duration::from_micros(micros.parse().ok()?);
+
+    let rest = parts.next()?;
+    if rest.contains("SYN_REPORT") {
+        return Some(InputEvent {
+            time,
+            ..InputEvent::new(EV_SYN, SYN_REPORT, 0)
+        });
+    }
+    let field = |part: &str, name: &str| -> Option<i64> {
+        part.strip_prefix(name)?
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()
+    };
+    let kind = field(rest, "type ")?;
+    let code = field(parts.next()?, "code ")?;
+    let value = field(parts.next()?, "value ")?;
+    Some(InputEvent {
+        time,
+        kind: kind as u16,
+        code: code as u16,
+        value: value as i32,
+    })
+}
+
+/// Replays a recording into a [`Bridge`] over time.
+///
+/// ```
+/// # use std::time::Duration;
+/// # use vcs_classic_hid_simulator::{gamepad::{codes, Bridge, InputEvent, Mapping, Recording, Replay}, SimulatedDevice};
+/// let press = |ms, value| InputEvent {
+///     time: Duration::from_millis(ms),
+///     ..InputEvent::new(codes::EV_KEY, codes::BTN_SOUTH, value)
+/// };
+/// let recording = Recording {
+///     ranges: Vec::new(),
+///     events: vec![press(1000, 1), press(1100, 0)],
+/// };
+///
+/// let mut device = SimulatedDevice::new();
+/// let mut bridge = Bridge::new(Mapping::default());
+/// let mut replay = Replay::new(recording);
+/// replay.update(Duration::from_millis(50), &mut bridge);
+/// bridge.apply(&mut device);
+/// assert!(device.is_button_1_down());
+/// replay.update(Duration::from_millis(150), &mut bridge);
+/// bridge.apply(&mut device);
+/// assert!(!device.is_button_1_down());
+/// assert!(replay.is_done());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Replay {
+    recording: Recording,
+    /// index of the next event to replay
+    next: usize,
+}
+
+impl Replay {
+    pub fn new(recording: Recording) -> Self {
+        Replay { recording, next: 0 }
+    }
+
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    /// Start the recording over.
+    pub fn reset(&mut self) {
+        self.next = 0;
+    }
+
+    /// Whether all events have been replayed.
+    pub fn is_done(&self) -> bool {
+        self.next >= self.recording.events.len()
+    }
+
+    /// Feed the bridge all events up to the given time
+    /// since the first event of the recording.
+    ///
+    /// The ranges of the recorded axes are given to the bridge
+    /// along with the first events.
+    pub fn update(&mut self, elapsed: Duration, bridge: &mut Bridge) {
+        let start = match self.recording.events.first() {
+            Some(event) => event.time,
+            None => return,
+        };
+        if self.next == 0 {
+            for (code, min, max) in &self.recording.ranges {
+                bridge.set_axis_range(*code, *min, *max);
+            }
+        }
+        while let Some(event) = self.recording.events.get(self.next) {
+            if event.time.saturating_sub(start) > elapsed {
+                break;
+            }
+            bridge.handle(event);
+            self.next += 1;
+        }
+    }
+}
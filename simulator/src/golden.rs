@@ -0,0 +1,175 @@
+//! Golden-file snapshots of LED frames.
+//!
+//! A sequence of frames is stored as text, one frame per line,
+//! with the Fuji LED followed by the 24 LEDs of the ring in hexadecimal:
+//!
+//! ```text
+//! # comments and empty lines are ignored
+//! FF | 00 00 80 FF 80 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00
+//! ```
+//!
+//! [`assert_golden`] compares frames against a stored file
+//! and fails with a readable diff of the frames which changed.
+//! Set the environment variable `UPDATE_GOLDEN=1` to write the file instead.
+//! A missing golden file is a failure otherwise,
+//! so that a deleted or mistyped snapshot is noticed.
+//!
+//! ```
+//! # use vcs_classic_hid::led::anims::Comet;
+//! # use vcs_classic_hid_simulator::{capture::record, golden};
+//! let frames = record(Comet::new(), 0..4);
+//! let text = golden::to_text(&frames);
+//! assert_eq!(golden::parse(&text)?, frames);
+//!
+//! let mut changed = frames.clone();
+//! changed[2].set(5, 0x42);
+//! let diff = golden::diff(&frames, &changed).unwrap();
+//! assert!(diff.contains("frame 2"));
+//! # Ok::<(), golden::ParseError>(())
+//! ```
+use std::{env, fs, path::Path};
+
+use vcs_classic_hid::{led::FUJI, LedReport};
+
+pub use crate::ParseError;
+
+/// The environment variable which, when set,
+/// makes [`assert_golden`] update the golden files.
+pub const UPDATE_VAR: &str = "UPDATE_GOLDEN";
+
+/// At most this many differing frames are shown in a diff
+const MAX_DIFF_FRAMES: usize = 10;
+
+/// Format a single frame as a line of text.
+fn frame_line(frame: &LedReport) -> String {
+    let ring: Vec<String> = frame.ring().iter().map(|v| format!("{:02X}", v)).collect();
+    format!("{:02X} | {}", frame.fuji(), ring.join(" "))
+}
+
+/// Convert frames to the golden text format.
+pub fn to_text(frames: &[LedReport]) -> String {
+    let mut text = String::new();
+    for frame in frames {
+        text.push_str(&frame_line(frame));
+        text.push('\n');
+    }
+    text
+}
+
+/// Parse frames from the golden text format.
+pub fn parse(text: &str) -> Result<Vec<LedReport>, ParseError> {
+    let mut frames = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let error = |message: String| ParseError {
+            line: i + 1,
+            message,
+        };
+        let (fuji, ring) = match line.split_once('|') {
+            Some(parts) => parts,
+            None => return Err(error("missing `|` after the Fuji LED".to_string())),
+        };
+        let value = |word: &str| {
+            u8::from_str_radix(word, 16).map_err(|_| error(format!("invalid LED value `{}`", word)))
+        };
+
+        let mut frame = LedReport::new();
+        frame.set(FUJI, value(fuji.trim())?);
+        let ring: Vec<&str> = ring.split_whitespace().collect();
+        if ring.len() != 24 {
+            return Err(error(format!("expected 24 ring LEDs, found {}", ring.len())));
+        }
+        for (led, word) in ring.into_iter().enumerate() {
+            frame.set(led as u8, value(word)?);
+        }
+        frames.push(frame);
+    }
+    Ok(frames)
+}
+
+/// Describe the differences between two sequences of frames,
+/// or return `None` if they are equal.
+///
+/// Differing LEDs are pointed at under each pair of frames.
+pub fn diff(expected: &[LedReport], actual: &[LedReport]) -> Option<String> {
+    let mut out = String::new();
+    if expected.len() != actual.len() {
+        out.push_str(&format!(
+            "expected {} frames, got {}\n",
+            expected.len(),
+            actual.len()
+        ));
+    }
+
+    let differing: Vec<usize> = (0..expected.len().min(actual.len()))
+        .filter(|&i| expected[i] != actual[i])
+        .collect();
+    for &i in differing.iter().take(MAX_DIFF_FRAMES) {
+        let (e, a) = (&expected[i], &actual[i]);
+        let mut marks = String::new();
+        marks.push_str(if e.fuji() != a.fuji() { "^^" } else { "  " });
+        marks.push_str("  ");
+        for led in 0..24 {
+            marks.push_str(if e.get(led) != a.get(led) { " ^^" } else { "   " });
+        }
+        out.push_str(&format!(
+            "frame {}:\n  expected: {}\n  actual:   {}\n            {}\n",
+            i,
+            frame_line(e),
+            frame_line(a),
+            marks.trim_end()
+        ));
+    }
+    if differing.len() > MAX_DIFF_FRAMES {
+        out.push_str(&format!(
+            "... and {} more differing frames\n",
+            differing.len() - MAX_DIFF_FRAMES
+        ));
+    }
+
+    if out.is_empty() {
+        None
+    } else {
+        Some(out)
+    }
+}
+
+/// Compare frames against a golden file, panicking with a diff if they differ.
+///
+/// The file is written instead if the environment variable `UPDATE_GOLDEN` is set,
+/// and it is an error for the file not to exist otherwise.
+pub fn assert_golden(path: impl AsRef<Path>, frames: &[LedReport]) {
+    let path = path.as_ref();
+    if env::var_os(UPDATE_VAR).is_some() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .unwrap_or_else(|e| panic!("could not create {}: {}", parent.display(), e));
+        }
+        fs::write(path, to_text(frames))
+            .unwrap_or_else(|e| panic!("could not write {}: {}", path.display(), e));
+        return;
+    }
+    if !path.exists() {
+        panic!(
+            "missing golden file {}, run with {}=1 to create it",
+            path.display(),
+            UPDATE_VAR
+        );
+    }
+
+    let text = fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("could not read {}: {}", path.display(), e));
+    let expected =
+        parse(&text).unwrap_or_else(|e| panic!("invalid golden file {}: {}", path.display(), e));
+    if let Some(diff) = diff(&expected, frames) {
+        panic!(
+            "LED frames differ from {}\n{}\nrun with {}=1 to update the golden file",
+            path.display(),
+            diff,
+            UPDATE_VAR
+        );
+    }
+}
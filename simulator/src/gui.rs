@@ -0,0 +1,303 @@
+//! Graphical front-end for the simulated device, via macroquad.
+//!
+//! Requires the `gui` feature.
+//! [`run_simulated`] opens a window showing the controller,
+//! maps the keyboard and mouse to its inputs,
+//! and calls the game's update function once per frame.
+//!
+//! | Input                        | Controller                   |
+//! |------------------------------|------------------------------|
+//! | arrow keys                   | stick                        |
+//! | Enter                        | button 1 (primary)           |
+//! | Space                        | button 2 (trigger)           |
+//! | Backspace                    | back button                  |
+//! | Right Shift                  | menu button                  |
+//! | F                            | Fuji button                  |
+//! | Q / E, mouse wheel           | roll the paddle              |
+//! | mouse drag around the ring   | roll the paddle to a point   |
+//!
+//! ```no_run
+//! use vcs_classic_hid::{Device, LedReport};
+//! use vcs_classic_hid_simulator::gui::run_simulated;
+//!
+//! run_simulated("My game", |device, _ticks| {
+//!     let on = if device.is_button_1_down() { 0xFF } else { 0 };
+//!     device.write(LedReport::filled(on)).is_ok()
+//! });
+//! ```
+use std::time::Duration;
+
+use macroquad::{prelude::*, Window};
+use vcs_classic_hid::{
+    input::StickPosition,
+    led::mapping::{position_to_roll, roll_to_position, ROLL_RANGE},
+    State,
+};
+
+use crate::SimulatedDevice;
+
+const RING_RADIUS: f32 = 200.;
+const STICK_RADIUS: f32 = 26.;
+/// how much the paddle rolls per frame while a key is held
+const ROLL_KEY_SPEED: i32 = 8;
+/// how much the paddle rolls per mouse wheel step
+const ROLL_WHEEL_SPEED: i32 = 16;
+
+/// Open a window with a simulated controller and run a game on it.
+///
+/// `update` is called once per frame
+/// with the simulated device and the current frame number,
+/// and should return `false` once the game has ended,
+/// which closes the window.
+pub fn run_simulated<F>(title: &str, mut update: F)
+where
+    F: FnMut(&mut SimulatedDevice, u64) -> bool + 'static,
+{
+    Window::new(title, async move {
+        let mut device = SimulatedDevice::new();
+        #[cfg(all(feature = "gamepad", target_os = "linux"))]
+        let mut gamepad = crate::gamepad::Gamepad::from_env();
+        let mut f: u64 = 0;
+
+        loop {
+            // -- input processing from keyboard and mouse --
+            let state = read_input(&device);
+            #[cfg(all(feature = "gamepad", target_os = "linux"))]
+            let state = crate::gamepad::merge_input(&mut gamepad, state);
+            // only send an input report when something changed
+            if state != device.state() {
+                device.push_state(state);
+            }
+
+            // -- game logic --
+            device.advance(Duration::from_secs_f32(get_frame_time()));
+            if !update(&mut device, f) {
+                break;
+            }
+
+            // -- rendering --
+            clear_background(Color {
+                r: 0.2,
+                g: 0.2,
+                b: 0.2,
+                a: 1.,
+            });
+
+            let header = format!(
+                "Frame: {:6} ({:3} FPS)  time: {:6.2}",
+                f,
+                get_fps(),
+                get_time()
+            );
+            draw_text(&header, 10., 20., 22., WHITE);
+            draw_text(
+                "Arrows: stick  Enter/Space: 1/2  Backspace: back  RShift: menu  F: Fuji  Q/E/wheel/drag: roll",
+                10.,
+                screen_height() - 12.,
+                18.,
+                LIGHTGRAY,
+            );
+
+            draw_device(&device);
+
+            f += 1;
+
+            next_frame().await
+        }
+    });
+}
+
+/// Obtain the new controller input state from the keyboard and mouse.
+fn read_input(device: &SimulatedDevice) -> State {
+    let key_left = is_key_down(KeyCode::Left);
+    let key_right = is_key_down(KeyCode::Right);
+    let key_up = is_key_down(KeyCode::Up);
+    let key_down = is_key_down(KeyCode::Down);
+
+    let stick_position = StickPosition::from_directions(key_up, key_right, key_down, key_left);
+
+    State {
+        stick_position,
+        button_1: is_key_down(KeyCode::Enter),
+        button_2: is_key_down(KeyCode::Space),
+        button_back: is_key_down(KeyCode::Backspace),
+        button_menu: is_key_down(KeyCode::RightShift),
+        button_fuji: is_key_down(KeyCode::F),
+        roll: read_roll(device.state().roll),
+    }
+}
+
+/// Obtain the new paddle roll from the keyboard and mouse.
+fn read_roll(roll: u16) -> u16 {
+    // dragging sets the roll to the point in the ring under the mouse
+    if is_mouse_button_down(MouseButton::Left) {
+        let (mouse_x, mouse_y) = mouse_position();
+        let dx = mouse_x - screen_width() / 2.;
+        let dy = mouse_y - screen_height() / 2.;
+        if dx.hypot(dy) > RING_RADIUS / 2. {
+            // inverse of `ring_point`
+            let angle = (-dx).atan2(dy);
+            return position_to_roll(angle / std::f32::consts::TAU * 24.);
+        }
+    }
+
+    let mut delta = 0;
+    if is_key_down(KeyCode::Q) {
+        delta -= ROLL_KEY_SPEED;
+    }
+    if is_key_down(KeyCode::E) {
+        delta += ROLL_KEY_SPEED;
+    }
+    let (_, wheel) = mouse_wheel();
+    if wheel > 0. {
+        delta += ROLL_WHEEL_SPEED;
+    } else if wheel < 0. {
+        delta -= ROLL_WHEEL_SPEED;
+    }
+
+    (i32::from(roll) + delta).rem_euclid(i32::from(ROLL_RANGE)) as u16
+}
+
+/// Draw the simulated classic controller on the current window.
+pub fn draw_device(device: &SimulatedDevice) {
+    let (shake_x, shake_y) = shake_offset(device.current_intensity());
+    let center_x = screen_width() / 2. + shake_x;
+    let center_y = screen_height() / 2. + shake_y;
+
+    let amp = 100.;
+    let amp2 = 80.;
+    let (stick_x, stick_y) = match device.stick_position() {
+        StickPosition::Up => (center_x, center_y - amp),
+        StickPosition::UpRight => (center_x + amp2, center_y - amp2),
+        StickPosition::Right => (center_x + amp, center_y),
+        StickPosition::DownRight => (center_x + amp2, center_y + amp2),
+        StickPosition::Down => (center_x, center_y + amp),
+        StickPosition::DownLeft => (center_x - amp2, center_y + amp2),
+        StickPosition::Left => (center_x - amp, center_y),
+        StickPosition::UpLeft => (center_x - amp2, center_y - amp2),
+        StickPosition::Center => (center_x, center_y),
+    };
+
+    draw_circle_lines(stick_x, stick_y, STICK_RADIUS, 2., BLACK);
+
+    draw_circle(
+        stick_x,
+        stick_y,
+        STICK_RADIUS,
+        Color {
+            r: 0.24,
+            g: 0.24,
+            b: 0.24,
+            a: 1.,
+        },
+    );
+
+    draw_led_ring(device.leds(), (center_x, center_y));
+    draw_roll(device.state().roll, (center_x, center_y));
+    draw_fuji_led(device.fuji_led(), device.is_button_fuji_down(), (center_x, center_y));
+    draw_buttons(device, (center_x, center_y));
+    draw_rumble(device.current_intensity());
+}
+
+/// The point in the ring at the given position,
+/// with LED 0 at the bottom and going clockwise.
+fn ring_point(position: f32, radius: f32, (center_x, center_y): (f32, f32)) -> (f32, f32) {
+    let angle = position * std::f32::consts::TAU / 24.;
+    (center_x - radius * angle.sin(), center_y + radius * angle.cos())
+}
+
+/// A jittering offset for the controller as it vibrates.
+fn shake_offset(intensity: u8) -> (f32, f32) {
+    if intensity == 0 {
+        return (0., 0.);
+    }
+    let amplitude = 6. * intensity as f32 / 255.;
+    let t = get_time() as f32;
+    (amplitude * (t * 97.).sin(), amplitude * (t * 83.).cos())
+}
+
+fn draw_fuji_led(led: u8, pressed: bool, (center_x, center_y): (f32, f32)) {
+    let pos_x = center_x;
+    let pos_y = center_y + RING_RADIUS + 40.;
+    draw_circle(
+        pos_x,
+        pos_y,
+        12.,
+        Color {
+            r: led as f32 / 255.,
+            g: led as f32 / 700.,
+            b: led as f32 / 1000.,
+            a: 1.,
+        },
+    );
+    let outline = if pressed { WHITE } else { BLACK };
+    draw_circle_lines(pos_x, pos_y, 12., 2., outline);
+}
+
+fn draw_led_ring(led_state: &[u8], center: (f32, f32)) {
+    for (i, led) in led_state.iter().copied().enumerate() {
+        let (pos_x, pos_y) = ring_point(i as f32, RING_RADIUS, center);
+        draw_circle(
+            pos_x,
+            pos_y,
+            16.,
+            Color {
+                r: led as f32 / 255.,
+                g: led as f32 / 1000.,
+                b: 0.,
+                a: 1.,
+            },
+        );
+
+        draw_text(&format!("{}", i), pos_x - 6., pos_y + 4., 18., BLACK);
+    }
+}
+
+/// Draw a marker outside the ring for the paddle roll.
+fn draw_roll(roll: u16, center: (f32, f32)) {
+    let position = roll_to_position(roll);
+    let (inner_x, inner_y) = ring_point(position, RING_RADIUS + 22., center);
+    let (outer_x, outer_y) = ring_point(position, RING_RADIUS + 38., center);
+    draw_line(inner_x, inner_y, outer_x, outer_y, 4., SKYBLUE);
+    draw_text(&format!("roll: {:4}", roll), 10., 44., 20., SKYBLUE);
+}
+
+/// Draw the controller's buttons, lit while pressed.
+fn draw_buttons(device: &SimulatedDevice, (center_x, center_y): (f32, f32)) {
+    let buttons = [
+        ("1", device.is_button_1_down(), -RING_RADIUS - 90., 0.),
+        ("2", device.is_button_2_down(), -RING_RADIUS - 90., 50.),
+        ("back", device.is_button_back_down(), -RING_RADIUS - 90., -RING_RADIUS),
+        ("menu", device.is_button_menu_down(), RING_RADIUS + 30., -RING_RADIUS),
+    ];
+    for (label, down, x, y) in buttons.iter().copied() {
+        let (x, y) = (center_x + x, center_y + y);
+        let fill = if down {
+            Color {
+                r: 0.9,
+                g: 0.9,
+                b: 0.9,
+                a: 1.,
+            }
+        } else {
+            Color {
+                r: 0.3,
+                g: 0.3,
+                b: 0.3,
+                a: 1.,
+            }
+        };
+        draw_rectangle(x, y, 60., 32., fill);
+        draw_rectangle_lines(x, y, 60., 32., 2., BLACK);
+        draw_text(label, x + 8., y + 22., 20., if down { BLACK } else { WHITE });
+    }
+}
+
+/// Draw a bar showing the current force feedback intensity.
+fn draw_rumble(intensity: u8) {
+    let width = 120.;
+    let x = screen_width() - width - 10.;
+    draw_text("rumble", x, 20., 20., WHITE);
+    draw_rectangle(x, 28., width * intensity as f32 / 255., 10., ORANGE);
+    draw_rectangle_lines(x, 28., width, 10., 1., WHITE);
+}
@@ -2,15 +2,120 @@
 //!
 //! Just create a [`SimulatedDevice`](crate::SimulatedDevice).
 //! Writes and reads can be performed as if it were the real device.
+//!
+//! The simulated device keeps its own clock,
+//! which only moves forward via [`advance`](SimulatedDevice::advance).
+//! Time-dependent behavior, such as force feedback,
+//! is modelled against this clock.
+//!
+//! Every change to the input state produces one input report,
+//! which is kept in a queue until read,
+//! like in the buffer of a real HID device.
+//! See [`OverflowPolicy`] for what happens when the queue is full.
+//!
+//! The device can also be made to fail on purpose,
+//! see the [`fault`] module.
+//!
+//! With the `gui` feature,
+//! the `gui` module provides a window for playing with the simulated device.
+//! With the `tui` feature, the `tui` module does the same in a terminal.
+//!
+//! For testing LED animations without a display,
+//! see the [`capture`], [`export`] and [`golden`] modules.
+//!
+//! The [`gamepad`] module maps a standard gamepad or keyboard
+//! onto the simulated device.
+//!
+//! To drive the simulated device from another process,
+//! such as a test harness in another language,
+//! see the [`net`] module.
+
+use std::{collections::VecDeque, fmt, time::Duration};
+
+use vcs_classic_hid::{
+    force_feedback::FfReport,
+    input::{State, StickPosition},
+    Device,
+};
+
+pub mod capture;
+pub mod export;
+pub mod fault;
+pub mod gamepad;
+pub mod golden;
+pub mod net;
+#[cfg(feature = "gui")]
+pub mod gui;
+pub mod script;
+#[cfg(all(feature = "tui", unix))]
+pub mod tui;
+
+use fault::Faults;
+
+/// The default number of input reports kept in the queue
+pub const DEFAULT_QUEUE_CAPACITY: usize = 64;
+
+/// What to do with a new input report
+/// when the queue of the simulated device is full.
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum OverflowPolicy {
+    /// Discard the new report,
+    /// as done by the Linux hidraw driver
+    #[default]
+    DropNewest,
+    /// Discard the oldest report in the queue to make room for the new one
+    DropOldest,
+}
 
-use vcs_classic_hid::{input::StickPosition, Device};
+/// Error type for when one of the simulator's text formats,
+/// such as [scripts](script) and [golden files](golden),
+/// could not be parsed.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseError {
+    /// The line where the error occurred, starting at 1
+    pub line: usize,
+    /// What went wrong
+    pub message: String,
+}
 
-#[derive(Debug, Default)]
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// An input report waiting to be read
+#[derive(Debug, Clone)]
+struct QueuedReport {
+    /// the time from which the report can be read
+    available_at: Duration,
+    data: Vec<u8>,
+}
+
+#[derive(Debug)]
 pub struct SimulatedDevice {
+    /// input reports not yet read
+    queue: VecDeque<QueuedReport>,
+    queue_capacity: usize,
+    overflow: OverflowPolicy,
+    /// number of input reports discarded due to a full queue
+    dropped: usize,
+    faults: Faults,
+    connected: bool,
+    /// successful reads and writes since the faults were set
+    reads: u64,
+    writes: u64,
+    clock: Duration,
+    /// the force feedback effect in place and when it started
+    ff_effect: Option<(Duration, FfReport)>,
+    /// all force feedback reports received and when
+    ff_log: Vec<(Duration, FfReport)>,
     stick_position: u8,
     stick_roll: u16,
-    empty_queue: bool,
     led_state: [u8; 24],
+    fuji_led_state: u8,
     button_1: bool,
     button_2: bool,
     button_back: bool,
@@ -18,15 +123,291 @@ pub struct SimulatedDevice {
     button_fuji: bool,
 }
 
+impl Default for SimulatedDevice {
+    fn default() -> Self {
+        Self::new_with_queue(DEFAULT_QUEUE_CAPACITY, OverflowPolicy::default())
+    }
+}
+
 impl SimulatedDevice {
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Create a simulated device
+    /// with the given input queue capacity and overflow policy.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use vcs_classic_hid::Device;
+    /// # use vcs_classic_hid_simulator::{OverflowPolicy, SimulatedDevice};
+    /// let mut device = SimulatedDevice::new_with_queue(2, OverflowPolicy::DropNewest);
+    /// device.set_button_1(true);
+    /// device.set_button_1(false);
+    /// device.set_button_2(true);
+    /// assert_eq!(device.queue_len(), 2);
+    /// assert_eq!(device.dropped_reports(), 1);
+    ///
+    /// // the tap on button 1 is not lost
+    /// let mut buf = [0; 6];
+    /// device.read(&mut buf)?;
+    /// assert_eq!(buf[1], 1);
+    /// device.read(&mut buf)?;
+    /// assert_eq!(buf[1], 0);
+    /// assert_eq!(device.read(&mut buf)?, 0);
+    /// # Ok::<(), &'static str>(())
+    /// ```
+    pub fn new_with_queue(capacity: usize, overflow: OverflowPolicy) -> Self {
+        SimulatedDevice {
+            queue: VecDeque::with_capacity(capacity),
+            queue_capacity: capacity,
+            overflow,
+            dropped: 0,
+            faults: Faults::default(),
+            connected: true,
+            reads: 0,
+            writes: 0,
+            clock: Duration::default(),
+            ff_effect: None,
+            ff_log: Vec::new(),
+            stick_position: 0,
+            stick_roll: 0,
+            led_state: [0; 24],
+            fuji_led_state: 0,
+            button_1: false,
+            button_2: false,
+            button_back: false,
+            button_menu: false,
+            button_fuji: false,
+        }
+    }
+
+    /// The maximum number of input reports kept in the queue.
+    pub fn queue_capacity(&self) -> usize {
+        self.queue_capacity
+    }
+
+    /// Change the maximum number of input reports kept in the queue.
+    ///
+    /// If the queue holds more reports than the new capacity,
+    /// the excess is discarded according to the overflow policy.
+    pub fn set_queue_capacity(&mut self, capacity: usize) {
+        self.queue_capacity = capacity;
+        while self.queue.len() > capacity {
+            match self.overflow {
+                OverflowPolicy::DropNewest => self.queue.pop_back(),
+                OverflowPolicy::DropOldest => self.queue.pop_front(),
+            };
+            self.dropped += 1;
+        }
+    }
+
+    pub fn overflow_policy(&self) -> OverflowPolicy {
+        self.overflow
+    }
+
+    pub fn set_overflow_policy(&mut self, overflow: OverflowPolicy) {
+        self.overflow = overflow;
+    }
+
+    /// The number of input reports waiting to be read.
+    pub fn queue_len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// The number of input reports discarded so far
+    /// because the queue was full.
+    pub fn dropped_reports(&self) -> usize {
+        self.dropped
+    }
+
+    /// Discard all input reports waiting to be read.
+    pub fn clear_queue(&mut self) {
+        self.queue.clear();
+    }
+
+    /// The faults currently being injected.
+    pub fn faults(&self) -> &Faults {
+        &self.faults
+    }
+
+    /// Start injecting the given faults.
+    ///
+    /// Operations are counted from this point onwards.
+    pub fn set_faults(&mut self, faults: Faults) {
+        self.faults = faults;
+        self.reads = 0;
+        self.writes = 0;
+    }
+
+    /// Whether the device is plugged in.
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// Simulate the device being unplugged.
+    ///
+    /// All reads and writes fail with [`fault::DISCONNECTED`]
+    /// and input changes are lost until the device is plugged in again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use vcs_classic_hid::{Device, LedReport};
+    /// # use vcs_classic_hid_simulator::{fault::DISCONNECTED, SimulatedDevice};
+    /// let mut device = SimulatedDevice::new();
+    /// device.write(LedReport::filled(0xFF))?;
+    /// device.unplug();
+    /// device.set_button_1(true);
+    /// assert_eq!(device.read(&mut [0; 6]), Err(DISCONNECTED));
+    ///
+    /// device.replug();
+    /// // the device starts over
+    /// assert_eq!(device.leds(), &[0; 24]);
+    /// assert_eq!(device.read(&mut [0; 6])?, 0);
+    /// # Ok::<(), &'static str>(())
+    /// ```
+    pub fn unplug(&mut self) {
+        self.connected = false;
+        self.queue.clear();
+    }
+
+    /// Simulate the device being plugged in again.
+    ///
+    /// Like the real device, it comes back with LEDs and force feedback off.
+    /// Inputs held while unplugged are kept,
+    /// but no report is produced for them.
+    pub fn replug(&mut self) {
+        self.connected = true;
+        self.led_state = [0; 24];
+        self.fuji_led_state = 0;
+        self.ff_effect = None;
+    }
+
+    /// Deliver an arbitrary input report,
+    /// such as garbage or a special report,
+    /// as the next report after the ones already in the queue.
+    ///
+    /// The report is subject to the queue's capacity,
+    /// but not to the device's latency.
+    pub fn inject_report(&mut self, data: &[u8]) {
+        self.push_report(QueuedReport {
+            available_at: self.clock,
+            data: data.to_vec(),
+        });
+    }
+
+    /// The current input state of the simulated device.
+    pub fn state(&self) -> State {
+        State {
+            stick_position: self.stick_position(),
+            button_1: self.button_1,
+            button_2: self.button_2,
+            button_back: self.button_back,
+            button_menu: self.button_menu,
+            button_fuji: self.button_fuji,
+            roll: self.stick_roll,
+        }
+    }
+
+    /// Replace the whole input state of the simulated device at once,
+    /// producing a single input report.
+    pub fn push_state(&mut self, state: State) {
+        self.stick_position = state.stick_position as u8;
+        self.stick_roll = state.roll & 0x3FF;
+        self.button_1 = state.button_1;
+        self.button_2 = state.button_2;
+        self.button_back = state.button_back;
+        self.button_menu = state.button_menu;
+        self.button_fuji = state.button_fuji;
+        // has new info
+        self.enqueue();
+    }
+
     pub fn leds(&self) -> &[u8; 24] {
         &self.led_state
     }
 
+    pub fn fuji_led(&self) -> u8 {
+        self.fuji_led_state
+    }
+
+    /// The current time in the simulated device's clock.
+    pub fn now(&self) -> Duration {
+        self.clock
+    }
+
+    /// Move the simulated device's clock forward.
+    pub fn advance(&mut self, delta: Duration) {
+        self.clock += delta;
+    }
+
+    /// Whether the device is vibrating at the current time.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use vcs_classic_hid::{Device, force_feedback::FfReport};
+    /// # use vcs_classic_hid_simulator::SimulatedDevice;
+    /// let mut device = SimulatedDevice::default();
+    /// // 2 pulses of 50ms on, 50ms off
+    /// FfReport::new_with_params(0xC0, 5, 5, 2).send(&mut device)?;
+    /// assert!(device.is_rumbling());
+    /// assert_eq!(device.current_intensity(), 0xC0);
+    /// device.advance(Duration::from_millis(60));
+    /// assert!(!device.is_rumbling());
+    /// device.advance(Duration::from_millis(50));
+    /// assert!(device.is_rumbling());
+    /// device.advance(Duration::from_millis(100));
+    /// assert!(!device.is_rumbling());
+    /// assert_eq!(device.ff_log().len(), 1);
+    /// # Ok::<(), &'static str>(())
+    /// ```
+    pub fn is_rumbling(&self) -> bool {
+        self.current_intensity() > 0
+    }
+
+    /// The intensity of the vibration at the current time,
+    /// 0 if not vibrating.
+    pub fn current_intensity(&self) -> u8 {
+        let (start, report) = match self.ff_effect {
+            Some(effect) => effect,
+            None => return 0,
+        };
+        let unit = vcs_classic_hid::force_feedback::TIME_UNIT;
+        let up = unit * u32::from(report.up_time());
+        let period = unit * (u32::from(report.up_time()) + u32::from(report.down_time()));
+        let elapsed = self.clock.saturating_sub(start);
+        if period.is_zero() || elapsed >= period * u32::from(report.times()) {
+            return 0;
+        }
+        let phase = Duration::from_nanos((elapsed.as_nanos() % period.as_nanos()) as u64);
+        if phase < up {
+            report.intensity()
+        } else {
+            0
+        }
+    }
+
+    /// The force feedback report currently in effect, if any,
+    /// and the time at which it was received.
+    pub fn ff_effect(&self) -> Option<(Duration, FfReport)> {
+        self.ff_effect
+    }
+
+    /// All force feedback reports received so far,
+    /// with the time at which they were received.
+    pub fn ff_log(&self) -> &[(Duration, FfReport)] {
+        &self.ff_log
+    }
+
+    /// Clear the log of force feedback reports received.
+    pub fn clear_ff_log(&mut self) {
+        self.ff_log.clear();
+    }
+
     pub fn move_stick(&mut self, position: u8) {
         self.stick_position = position;
         // has new info
@@ -93,9 +474,46 @@ impl SimulatedDevice {
         StickPosition::from_u8(self.stick_position).unwrap()
     }
 
-    #[inline]
+    /// The controller input report for the current state,
+    /// as it would be read from the device.
+    pub fn input_report(&self) -> [u8; 5] {
+        [
+            1,
+            self.button_1 as u8 | (self.button_2 as u8) << 1,
+            self.button_back as u8
+                | ((self.button_fuji as u8) << 2)
+                | self.stick_position << 4
+                | ((self.button_menu as u8) << 1),
+            self.stick_roll as u8,
+            (self.stick_roll >> 8) as u8,
+        ]
+    }
+
     fn enqueue(&mut self) {
-        self.empty_queue = false;
+        if !self.connected {
+            return;
+        }
+        let report = QueuedReport {
+            available_at: self.clock + self.faults.latency,
+            data: self.input_report().to_vec(),
+        };
+        self.push_report(report);
+    }
+
+    fn push_report(&mut self, report: QueuedReport) {
+        if self.queue.len() >= self.queue_capacity {
+            self.dropped += 1;
+            match self.overflow {
+                OverflowPolicy::DropNewest => return,
+                OverflowPolicy::DropOldest => {
+                    if self.queue.pop_front().is_none() {
+                        // zero capacity
+                        return;
+                    }
+                }
+            }
+        }
+        self.queue.push_back(report);
     }
 }
 
@@ -108,9 +526,20 @@ impl Device for SimulatedDevice {
     }
 
     fn read(&mut self, out: &mut [u8]) -> Result<usize, Self::Error> {
-        if self.empty_queue {
+        if !self.connected {
+            return Err(fault::DISCONNECTED);
+        }
+        if let Some(n) = self.faults.fail_reads_after {
+            if self.reads >= n {
+                return Err(fault::READ_ERROR);
+            }
+        }
+        self.reads += 1;
+
+        match self.queue.front() {
+            Some(report) if report.available_at <= self.clock => {}
             // no new input to consume
-            return Ok(0);
+            _ => return Ok(0),
         }
 
         if out.len() < 6 {
@@ -118,45 +547,69 @@ impl Device for SimulatedDevice {
             return Ok(0);
         }
 
-        // produce controller input report
-        out[0] = 1;
-        out[1] = self.button_1 as u8 | (self.button_2 as u8) << 1;
-        out[2] = self.button_back as u8
-            | ((self.button_fuji as u8) << 2)
-            | self.stick_position << 4
-            | ((self.button_menu as u8) << 1);
-        out[3] = self.stick_roll as u8;
-        out[4] = (self.stick_roll >> 8) as u8;
-        out[5] = 0;
-
-        // force the queue to be empty
-        // so that the next request for data suggests that the queue is empty
-        self.empty_queue = true;
-        Ok(5)
+        let report = self.queue.pop_front().unwrap();
+        let len = report.data.len().min(out.len());
+        out[..len].copy_from_slice(&report.data[..len]);
+        Ok(len)
     }
 
     fn write<T>(&mut self, data: T) -> Result<usize, Self::Error>
     where
         T: AsRef<[u8]>,
     {
+        if !self.connected {
+            return Err(fault::DISCONNECTED);
+        }
+        if let Some(n) = self.faults.fail_writes_after {
+            if self.writes >= n {
+                return Err(fault::WRITE_ERROR);
+            }
+        }
+        self.writes += 1;
+
         let data = data.as_ref();
+        let data = match self.faults.max_write_len {
+            Some(max) => &data[..data.len().min(max)],
+            None => data,
+        };
         match data.get(0) {
             None => {
                 eprintln!("No report was sent");
                 Ok(0)
             }
+            Some(1) => {
+                // force feedback report
+                match FfReport::from_bytes(data) {
+                    Some(report) => {
+                        self.ff_log.push((self.clock, report));
+                        self.ff_effect = Some((self.clock, report));
+                        Ok(data.len())
+                    }
+                    None => {
+                        eprintln!("Force feedback report too short");
+                        Ok(0)
+                    }
+                }
+            }
             Some(2) => {
                 // LED report
-                data.get(1).map(|l| {
+                if let Some(l) = data.get(1) {
                     let l = *l as usize;
-                    // note, data[2] is ignored for now
-                    for (led, d) in
-                        std::iter::Iterator::zip(self.led_state[..].iter_mut(), data[3..].iter())
-                            .take(l)
+                    // the first LED is the Fuji LED, followed by the ring
+                    if l > 0 {
+                        if let Some(fuji) = data.get(2) {
+                            self.fuji_led_state = *fuji;
+                        }
+                    }
+                    for (led, d) in std::iter::Iterator::zip(
+                        self.led_state[..].iter_mut(),
+                        data.iter().skip(3),
+                    )
+                    .take(l.saturating_sub(1))
                     {
                         *led = *d;
                     }
-                });
+                }
 
                 Ok(data.len())
             }
@@ -0,0 +1,605 @@
+//! Remote control of a simulated device over a local socket.
+//!
+//! A [`SharedDevice`] is a [`SimulatedDevice`] which can be used
+//! by the program under test through the usual [`Device`] trait
+//! while a [`Server`] lets other processes,
+//! such as test harnesses written in other languages,
+//! push input and observe the device over TCP or a Unix socket.
+//!
+//! # Protocol
+//!
+//! Requests and responses are JSON objects, one per line.
+//! Every request has a `cmd` member and may have an `id`,
+//! which is copied into the response.
+//! Responses have `"ok": true` and the members listed below,
+//! or `"ok": false` and an `error` message.
+//!
+//! | `cmd`         | Arguments                  | Response members                     |
+//! |---------------|----------------------------|--------------------------------------|
+//! | `state`       |                            | `state`: the input state             |
+//! | `push`        | `state`: input state       |                                      |
+//! | `leds`        |                            | `fuji`, `ring`: the LED values       |
+//! | `ff`          |                            | `rumbling`, `intensity`, `effect`    |
+//! | `advance`     | `ms`: milliseconds         | `time_ms`: the device's clock        |
+//! | `unplug`      |                            |                                      |
+//! | `replug`      |                            |                                      |
+//! | `subscribe`   |                            |                                      |
+//! | `unsubscribe` |                            |                                      |
+//!
+//! An input state is an object with the members
+//! `stick_position` (`center`, `up`, `up-right`, `right`, ...),
+//! `button_1`, `button_2`, `button_back`, `button_menu`, `button_fuji`
+//! and `roll` (0 to 1023).
+//! When pushing a state, members left out keep their current value.
+//! The `effect` of `ff` is the last force feedback report written,
+//! with `time_ms`, `intensity`, `up_time`, `down_time` and `times`,
+//! or `null`.
+//!
+//! After `subscribe`, every report written to the device
+//! is also sent to the client as an event line,
+//! such as `{"event":"write","time_ms":0,"data":[2,25,...]}`.
+//!
+//! # Example
+//!
+//! ```
+//! # use std::{io::{BufRead, BufReader, Write}, net::TcpStream};
+//! # use vcs_classic_hid::{process_input, Device, LedReport, StickPosition};
+//! # use vcs_classic_hid_simulator::net::{Server, SharedDevice};
+//! let device = SharedDevice::default();
+//! let server = Server::bind_tcp("127.0.0.1:0", device.clone())?;
+//!
+//! // the test harness
+//! let mut client = TcpStream::connect(server.local_addr().unwrap())?;
+//! let mut lines = BufReader::new(client.try_clone()?).lines();
+//! writeln!(client, r#"{{"id": 1, "cmd": "push", "state": {{"stick_position": "up"}}}}"#)?;
+//! assert_eq!(lines.next().unwrap()?, r#"{"id":1,"ok":true}"#);
+//! writeln!(client, r#"{{"cmd": "subscribe"}}"#)?;
+//! assert_eq!(lines.next().unwrap()?, r#"{"ok":true}"#);
+//!
+//! // the program under test
+//! let mut app_device = device.clone();
+//! let state = process_input(&mut app_device).unwrap().unwrap();
+//! assert_eq!(state.stick_position, StickPosition::Up);
+//! app_device.write(&[2, 2, 0xFF, 0x80]).unwrap();
+//!
+//! let event = lines.next().unwrap()?;
+//! assert_eq!(event, r#"{"event":"write","time_ms":0,"data":[2,2,255,128]}"#);
+//! writeln!(client, r#"{{"cmd": "leds"}}"#)?;
+//! assert!(lines.next().unwrap()?.starts_with(r#"{"ok":true,"fuji":255,"ring":[128,0,"#));
+//!
+//! server.stop();
+//! # Ok::<(), std::io::Error>(())
+//! ```
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc, Mutex, MutexGuard,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+
+use vcs_classic_hid::{Device, State};
+
+use crate::{
+    script::{stick_from_name, stick_name},
+    SimulatedDevice,
+};
+
+mod json;
+
+use json::Value;
+
+/// How often blocked threads check whether they should stop
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// A report written to a [`SharedDevice`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct WriteEvent {
+    /// The time of the write in the simulated device's clock
+    pub time: Duration,
+    /// The report data, including the report ID
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    device: Mutex<SimulatedDevice>,
+    subscribers: Mutex<Vec<Sender<WriteEvent>>>,
+}
+
+/// A simulated device which can be shared across threads.
+///
+/// Clones refer to the same device.
+/// The device can be used through the [`Device`] trait as usual,
+/// or inspected and manipulated directly with [`lock`](SharedDevice::lock).
+#[derive(Debug, Clone)]
+pub struct SharedDevice {
+    shared: Arc<Shared>,
+}
+
+impl Default for SharedDevice {
+    fn default() -> Self {
+        SharedDevice::new(SimulatedDevice::new())
+    }
+}
+
+impl SharedDevice {
+    pub fn new(device: SimulatedDevice) -> Self {
+        SharedDevice {
+            shared: Arc::new(Shared {
+                device: Mutex::new(device),
+                subscribers: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Obtain exclusive access to the simulated device.
+    pub fn lock(&self) -> MutexGuard<'_, SimulatedDevice> {
+        self.shared.device.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Receive an event for every report written to the device from now on.
+    ///
+    /// The subscription ends when the receiver is dropped.
+    pub fn subscribe(&self) -> Receiver<WriteEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers().push(sender);
+        receiver
+    }
+
+    fn subscribers(&self) -> MutexGuard<'_, Vec<Sender<WriteEvent>>> {
+        self.shared
+            .subscribers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Device for SharedDevice {
+    type Error = &'static str;
+
+    fn set_blocking(&mut self, blocking: bool) -> Result<(), Self::Error> {
+        self.lock().set_blocking(blocking)
+    }
+
+    fn read(&mut self, out: &mut [u8]) -> Result<usize, Self::Error> {
+        self.lock().read(out)
+    }
+
+    fn write<T>(&mut self, data: T) -> Result<usize, Self::Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        let data = data.as_ref();
+        let (len, time) = {
+            let mut device = self.lock();
+            (device.write(data)?, device.now())
+        };
+        let event = WriteEvent {
+            time,
+            data: data.to_vec(),
+        };
+        self.subscribers()
+            .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        Ok(len)
+    }
+}
+
+/// A stream accepted by the server.
+trait Connection: Read + Write + Send + Sized + 'static {
+    fn try_clone(&self) -> io::Result<Self>;
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()>;
+    fn shutdown(&self) -> io::Result<()>;
+}
+
+impl Connection for TcpStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        TcpStream::try_clone(self)
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        TcpStream::set_nonblocking(self, nonblocking)
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        TcpStream::shutdown(self, std::net::Shutdown::Both)
+    }
+}
+
+#[cfg(unix)]
+impl Connection for UnixStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        UnixStream::try_clone(self)
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        UnixStream::set_nonblocking(self, nonblocking)
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        UnixStream::shutdown(self, std::net::Shutdown::Both)
+    }
+}
+
+/// A listener for connections to the server.
+trait Listener: Send + 'static {
+    type Stream: Connection;
+
+    fn accept(&self) -> io::Result<Self::Stream>;
+}
+
+impl Listener for TcpListener {
+    type Stream = TcpStream;
+
+    fn accept(&self) -> io::Result<TcpStream> {
+        TcpListener::accept(self).map(|(stream, _)| stream)
+    }
+}
+
+#[cfg(unix)]
+impl Listener for UnixListener {
+    type Stream = UnixStream;
+
+    fn accept(&self) -> io::Result<UnixStream> {
+        UnixListener::accept(self).map(|(stream, _)| stream)
+    }
+}
+
+/// A server exposing a [`SharedDevice`] to other processes,
+/// running on its own threads.
+///
+/// Each client is served on a separate thread.
+/// See the [module documentation](self) for the protocol.
+#[derive(Debug)]
+pub struct Server {
+    local_addr: Option<SocketAddr>,
+    socket_path: Option<PathBuf>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Server {
+    /// Start serving the device over TCP at the given address.
+    ///
+    /// Use port 0 to let the system choose a free port,
+    /// then check it with [`local_addr`](Server::local_addr).
+    pub fn bind_tcp(addr: impl ToSocketAddrs, device: SharedDevice) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+        let mut server = Server::spawn(listener, device);
+        server.local_addr = Some(local_addr);
+        Ok(server)
+    }
+
+    /// Start serving the device over a Unix socket at the given path.
+    ///
+    /// The socket file is removed when the server stops.
+    #[cfg(unix)]
+    pub fn bind_unix(path: impl AsRef<Path>, device: SharedDevice) -> io::Result<Self> {
+        let path = path.as_ref();
+        let listener = UnixListener::bind(path)?;
+        listener.set_nonblocking(true)?;
+        let mut server = Server::spawn(listener, device);
+        server.socket_path = Some(path.to_path_buf());
+        Ok(server)
+    }
+
+    fn spawn<L>(listener: L, device: SharedDevice) -> Self
+    where
+        L: Listener,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let stop = Arc::clone(&stop);
+            std::thread::spawn(move || accept_loop(listener, device, &stop))
+        };
+        Server {
+            local_addr: None,
+            socket_path: None,
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    /// The address of the server, if serving over TCP.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+
+    /// The path to the socket, if serving over a Unix socket.
+    pub fn socket_path(&self) -> Option<&Path> {
+        self.socket_path.as_deref()
+    }
+
+    /// Disconnect all clients and stop serving.
+    ///
+    /// This is also done when the server is dropped.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            self.stop.store(true, Ordering::Release);
+            let _ = handle.join();
+            if let Some(path) = &self.socket_path {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn accept_loop<L>(listener: L, device: SharedDevice, stop: &AtomicBool)
+where
+    L: Listener,
+{
+    let mut clients: Vec<(L::Stream, JoinHandle<()>)> = Vec::new();
+    while !stop.load(Ordering::Acquire) {
+        match listener.accept() {
+            Ok(stream) => {
+                let client = stream
+                    .set_nonblocking(false)
+                    .and_then(|_| stream.try_clone());
+                match client {
+                    Ok(client) => {
+                        let device = device.clone();
+                        let handle = std::thread::spawn(move || {
+                            if let Err(e) = serve(stream, &device) {
+                                eprintln!("Simulator client error: {}", e);
+                            }
+                        });
+                        clients.push((client, handle));
+                    }
+                    Err(e) => eprintln!("Could not set up simulator client: {}", e),
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => std::thread::sleep(POLL_INTERVAL),
+            Err(e) => {
+                eprintln!("Could not accept simulator client: {}", e);
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        }
+        clients.retain(|(_, handle)| !handle.is_finished());
+    }
+
+    for (client, handle) in clients {
+        let _ = client.shutdown();
+        let _ = handle.join();
+    }
+}
+
+/// Write events to a client on a separate thread.
+struct Subscription {
+    active: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+impl Subscription {
+    fn start<W>(events: Receiver<WriteEvent>, writer: Arc<Mutex<W>>) -> Self
+    where
+        W: Write + Send + 'static,
+    {
+        let active = Arc::new(AtomicBool::new(true));
+        let handle = {
+            let active = Arc::clone(&active);
+            std::thread::spawn(move || {
+                while active.load(Ordering::Acquire) {
+                    let event = match events.recv_timeout(POLL_INTERVAL) {
+                        Ok(event) => event,
+                        Err(RecvTimeoutError::Timeout) => continue,
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    };
+                    let line = Value::object(vec![
+                        ("event", Value::from("write")),
+                        ("time_ms", Value::from(event.time.as_millis() as u64)),
+                        ("data", Value::from(&event.data[..])),
+                    ]);
+                    if send_line(&writer, &line).is_err() {
+                        break;
+                    }
+                }
+            })
+        };
+        Subscription { active, handle }
+    }
+
+    fn stop(self) {
+        self.active.store(false, Ordering::Release);
+        let _ = self.handle.join();
+    }
+}
+
+fn send_line<W>(writer: &Mutex<W>, value: &Value) -> io::Result<()>
+where
+    W: Write,
+{
+    let mut writer = writer.lock().unwrap_or_else(|e| e.into_inner());
+    writeln!(writer, "{}", value)?;
+    writer.flush()
+}
+
+/// Serve a client until it disconnects.
+fn serve<S>(stream: S, device: &SharedDevice) -> io::Result<()>
+where
+    S: Connection,
+{
+    let writer = Arc::new(Mutex::new(stream.try_clone()?));
+    let mut subscription: Option<Subscription> = None;
+
+    let result = (|| {
+        for line in BufReader::new(stream).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let request = Value::parse(&line);
+            let mut response = Vec::new();
+            if let Some(id) = request.as_ref().ok().and_then(|r| r.get("id")) {
+                response.push(("id", id.clone()));
+            }
+            let mut events = None;
+            let result = match &request {
+                Err(e) => Err(format!("invalid JSON: {}", e)),
+                Ok(request) => match request.get("cmd").and_then(Value::as_str) {
+                    None => Err("missing `cmd`".to_string()),
+                    Some("subscribe") => {
+                        if subscription.is_none() {
+                            events = Some(device.subscribe());
+                        }
+                        Ok(Vec::new())
+                    }
+                    Some("unsubscribe") => {
+                        if let Some(subscription) = subscription.take() {
+                            subscription.stop();
+                        }
+                        Ok(Vec::new())
+                    }
+                    Some(cmd) => run_command(cmd, request, device),
+                },
+            };
+            match result {
+                Ok(members) => {
+                    response.push(("ok", Value::Bool(true)));
+                    response.extend(members);
+                }
+                Err(message) => {
+                    response.push(("ok", Value::Bool(false)));
+                    response.push(("error", Value::String(message)));
+                }
+            }
+            send_line(&writer, &Value::object(response))?;
+
+            // only send events after confirming the subscription
+            if let Some(events) = events {
+                subscription = Some(Subscription::start(events, Arc::clone(&writer)));
+            }
+        }
+        Ok(())
+    })();
+
+    if let Some(subscription) = subscription {
+        subscription.stop();
+    }
+    result
+}
+
+/// Run a command which does not concern the connection itself.
+fn run_command(
+    cmd: &str,
+    request: &Value,
+    device: &SharedDevice,
+) -> Result<Vec<(&'static str, Value)>, String> {
+    let mut device = device.lock();
+    match cmd {
+        "state" => Ok(vec![("state", state_to_json(&device.state()))]),
+        "push" => {
+            let state = request
+                .get("state")
+                .ok_or_else(|| "missing `state`".to_string())?;
+            let state = state_from_json(state, device.state())?;
+            device.push_state(state);
+            Ok(Vec::new())
+        }
+        "leds" => Ok(vec![
+            ("fuji", Value::from(device.fuji_led())),
+            ("ring", Value::from(&device.leds()[..])),
+        ]),
+        "ff" => {
+            let effect = device.ff_effect().map(|(time, report)| {
+                Value::object(vec![
+                    ("time_ms", Value::from(time.as_millis() as u64)),
+                    ("intensity", Value::from(report.intensity())),
+                    ("up_time", Value::from(report.up_time())),
+                    ("down_time", Value::from(report.down_time())),
+                    ("times", Value::from(report.times())),
+                ])
+            });
+            Ok(vec![
+                ("rumbling", Value::from(device.is_rumbling())),
+                ("intensity", Value::from(device.current_intensity())),
+                ("effect", Value::from(effect)),
+            ])
+        }
+        "advance" => {
+            let ms = request
+                .get("ms")
+                .and_then(Value::as_u64)
+                .ok_or_else(|| "missing or invalid `ms`".to_string())?;
+            device.advance(Duration::from_millis(ms));
+            Ok(vec![(
+                "time_ms",
+                Value::from(device.now().as_millis() as u64),
+            )])
+        }
+        "unplug" => {
+            device.unplug();
+            Ok(Vec::new())
+        }
+        "replug" => {
+            device.replug();
+            Ok(Vec::new())
+        }
+        cmd => Err(format!("unknown command `{}`", cmd)),
+    }
+}
+
+fn state_to_json(state: &State) -> Value {
+    Value::object(vec![
+        ("stick_position", Value::from(stick_name(state.stick_position))),
+        ("button_1", Value::from(state.button_1)),
+        ("button_2", Value::from(state.button_2)),
+        ("button_back", Value::from(state.button_back)),
+        ("button_menu", Value::from(state.button_menu)),
+        ("button_fuji", Value::from(state.button_fuji)),
+        ("roll", Value::from(state.roll)),
+    ])
+}
+
+/// Update an input state with the members of a JSON object.
+fn state_from_json(value: &Value, mut state: State) -> Result<State, String> {
+    let members = match value {
+        Value::Object(members) => members,
+        _ => return Err("`state` must be an object".to_string()),
+    };
+    for (key, value) in members {
+        let invalid = || format!("invalid value for `{}`", key);
+        let flag = || value.as_bool().ok_or_else(invalid);
+        match key.as_str() {
+            "stick_position" => {
+                state.stick_position = value
+                    .as_str()
+                    .and_then(stick_from_name)
+                    .ok_or_else(invalid)?
+            }
+            "button_1" => state.button_1 = flag()?,
+            "button_2" => state.button_2 = flag()?,
+            "button_back" => state.button_back = flag()?,
+            "button_menu" => state.button_menu = flag()?,
+            "button_fuji" => state.button_fuji = flag()?,
+            "roll" => {
+                state.roll = value
+                    .as_u64()
+                    .filter(|roll| *roll < 1024)
+                    .ok_or_else(invalid)? as u16
+            }
+            key => return Err(format!("unknown state member `{}`", key)),
+        }
+    }
+    Ok(state)
+}
@@ -0,0 +1,469 @@
+//! Scripted input timelines for the simulated device.
+//!
+//! A [`Script`] is a list of input actions, each at a given tick.
+//! A [`ScriptPlayer`] applies them to a [`SimulatedDevice`]
+//! as the ticks go by, which makes it possible
+//! to reproduce a play session in automated tests.
+//!
+//! # Example
+//!
+//! ```
+//! # use vcs_classic_hid::StickPosition;
+//! # use vcs_classic_hid_simulator::SimulatedDevice;
+//! # use vcs_classic_hid_simulator::script::{Button, Script, ScriptPlayer};
+//! let script = Script::new()
+//!     .stick(30, StickPosition::Up)
+//!     .stick(45, StickPosition::Center)
+//!     .press(50, Button::One)
+//!     .sweep(60, 512, 20);
+//!
+//! let mut device = SimulatedDevice::new();
+//! let mut player = ScriptPlayer::new(script);
+//! for tick in 0..=40 {
+//!     player.update(tick, &mut device);
+//! }
+//! assert_eq!(device.stick_position(), StickPosition::Up);
+//! for tick in 41..=70 {
+//!     player.update(tick, &mut device);
+//! }
+//! assert_eq!(device.stick_position(), StickPosition::Center);
+//! assert!(device.is_button_1_down());
+//! assert_eq!(device.state().roll, 256);
+//! assert!(!player.is_done());
+//! for tick in 71..=80 {
+//!     player.update(tick, &mut device);
+//! }
+//! assert_eq!(device.state().roll, 512);
+//! assert!(player.is_done());
+//! ```
+//!
+//! # Text format
+//!
+//! Scripts can also be written in a small text format,
+//! with one action per line in the form `<tick> <action> [arguments]`.
+//! Empty lines and anything after a `#` are ignored.
+//!
+//! | Action                    | Meaning                                      |
+//! |---------------------------|----------------------------------------------|
+//! | `press <button>`          | press a button (`1`, `2`, `back`, `menu`, `fuji`) |
+//! | `release <button>`        | release a button                             |
+//! | `press <direction>`       | move the stick (`up`, `up-right`, `right`, ...) |
+//! | `release <direction>`     | move the stick back to the center            |
+//! | `stick <direction>`       | move the stick (`center` included)           |
+//! | `roll <value>`            | set the paddle roll (0 to 1023)              |
+//! | `sweep <value> <ticks>`   | roll the paddle smoothly to a value          |
+//!
+//! ```
+//! # use vcs_classic_hid_simulator::script::Script;
+//! let script: Script = "
+//!     30 press up  # select the first option
+//!     45 release up
+//!     50 press 1
+//!     52 release 1
+//!     60 sweep 512 20
+//! ".parse()?;
+//! assert_eq!(script.len(), 5);
+//! assert_eq!(script.duration(), 80);
+//!
+//! // scripts can be printed back into the same format
+//! let text = script.to_string();
+//! assert_eq!(text.parse::<Script>()?, script);
+//! # Ok::<(), vcs_classic_hid_simulator::script::ParseError>(())
+//! ```
+//!
+//! Random scripts can be generated with [`Script::random`]
+//! to fuzz a game with a reproducible sequence of inputs.
+//!
+//! ```
+//! # use vcs_classic_hid_simulator::script::Script;
+//! let script = Script::random(42, 600);
+//! assert_eq!(script, Script::random(42, 600));
+//! assert!(script.duration() <= 600);
+//! ```
+use std::{fmt, str::FromStr};
+
+use vcs_classic_hid::{rand::XorShift32, StickPosition};
+
+pub use crate::ParseError;
+use crate::SimulatedDevice;
+
+/// A button on the controller.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum Button {
+    One,
+    Two,
+    Back,
+    Menu,
+    Fuji,
+}
+
+impl Button {
+    const ALL: [Button; 5] = [
+        Button::One,
+        Button::Two,
+        Button::Back,
+        Button::Menu,
+        Button::Fuji,
+    ];
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Button::One => "1",
+            Button::Two => "2",
+            Button::Back => "back",
+            Button::Menu => "menu",
+            Button::Fuji => "fuji",
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        Button::ALL.iter().copied().find(|b| b.name() == name)
+    }
+}
+
+const STICK_NAMES: [&str; 9] = [
+    "center",
+    "up",
+    "up-right",
+    "right",
+    "down-right",
+    "down",
+    "down-left",
+    "left",
+    "up-left",
+];
+
+pub(crate) fn stick_name(position: StickPosition) -> &'static str {
+    STICK_NAMES[position as usize]
+}
+
+pub(crate) fn stick_from_name(name: &str) -> Option<StickPosition> {
+    let index = STICK_NAMES.iter().position(|n| *n == name)?;
+    StickPosition::from_u8(index as u8)
+}
+
+/// A single input action.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum Action {
+    /// Press a button
+    Press(Button),
+    /// Release a button
+    Release(Button),
+    /// Move the stick to a position
+    Stick(StickPosition),
+    /// Set the paddle roll, from 0 to 1023
+    Roll(u16),
+    /// Roll the paddle linearly from its current value
+    /// to the given value over a number of ticks
+    Sweep { to: u16, ticks: u64 },
+}
+
+impl Action {
+    fn apply(self, device: &mut SimulatedDevice) {
+        match self {
+            Action::Press(button) | Action::Release(button) => {
+                let down = matches!(self, Action::Press(_));
+                match button {
+                    Button::One => device.set_button_1(down),
+                    Button::Two => device.set_button_2(down),
+                    Button::Back => device.set_button_back(down),
+                    Button::Menu => device.set_button_menu(down),
+                    Button::Fuji => device.set_button_fuji(down),
+                }
+            }
+            Action::Stick(position) => device.move_stick(position as u8),
+            Action::Roll(roll) => device.set_roll(roll),
+            // handled by the player
+            Action::Sweep { .. } => {}
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Action::Press(button) => write!(f, "press {}", button.name()),
+            Action::Release(button) => write!(f, "release {}", button.name()),
+            Action::Stick(position) => write!(f, "stick {}", stick_name(*position)),
+            Action::Roll(roll) => write!(f, "roll {}", roll),
+            Action::Sweep { to, ticks } => write!(f, "sweep {} {}", to, ticks),
+        }
+    }
+}
+
+/// A timeline of input actions.
+///
+/// Actions are kept sorted by tick.
+/// Actions at the same tick are applied in the order they were added.
+#[derive(Debug, Default, Clone, Eq, Hash, PartialEq)]
+pub struct Script {
+    events: Vec<(u64, Action)>,
+}
+
+impl Script {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an action at the given tick.
+    pub fn at(mut self, tick: u64, action: Action) -> Self {
+        let index = self.events.partition_point(|(t, _)| *t <= tick);
+        self.events.insert(index, (tick, action));
+        self
+    }
+
+    /// Press a button at the given tick.
+    pub fn press(self, tick: u64, button: Button) -> Self {
+        self.at(tick, Action::Press(button))
+    }
+
+    /// Release a button at the given tick.
+    pub fn release(self, tick: u64, button: Button) -> Self {
+        self.at(tick, Action::Release(button))
+    }
+
+    /// Press a button at the given tick and release it `hold` ticks later.
+    pub fn tap(self, tick: u64, button: Button, hold: u64) -> Self {
+        self.press(tick, button).release(tick + hold, button)
+    }
+
+    /// Move the stick at the given tick.
+    pub fn stick(self, tick: u64, position: StickPosition) -> Self {
+        self.at(tick, Action::Stick(position))
+    }
+
+    /// Set the paddle roll at the given tick.
+    pub fn roll(self, tick: u64, roll: u16) -> Self {
+        self.at(tick, Action::Roll(roll))
+    }
+
+    /// Roll the paddle smoothly to a value,
+    /// starting at the given tick and lasting `ticks` ticks.
+    pub fn sweep(self, tick: u64, to: u16, ticks: u64) -> Self {
+        self.at(tick, Action::Sweep { to, ticks })
+    }
+
+    /// Generate a random script lasting the given number of ticks.
+    ///
+    /// The same seed always produces the same script.
+    /// The Fuji button is never pressed,
+    /// since games usually quit when it is.
+    pub fn random(seed: u32, ticks: u64) -> Self {
+        let mut rng = XorShift32::new(seed);
+        let mut script = Script::new();
+        let mut tick = 0;
+        loop {
+            // an action every 1 to 16 ticks
+            tick += u64::from(rng.next_u32() % 16) + 1;
+            if tick >= ticks {
+                break;
+            }
+            let action = match rng.next_u32() % 8 {
+                0..=2 => {
+                    let button = Button::ALL[(rng.next_u32() % 4) as usize];
+                    if rng.next_u32().is_multiple_of(2) {
+                        Action::Press(button)
+                    } else {
+                        Action::Release(button)
+                    }
+                }
+                3..=5 => Action::Stick(StickPosition::from_u8((rng.next_u32() % 9) as u8).unwrap()),
+                6 => Action::Roll((rng.next_u32() % 1024) as u16),
+                _ => Action::Sweep {
+                    to: (rng.next_u32() % 1024) as u16,
+                    ticks: u64::from(rng.next_u32() % 32).min(ticks - tick),
+                },
+            };
+            script = script.at(tick, action);
+        }
+        script
+    }
+
+    /// The actions in the script and their ticks.
+    pub fn events(&self) -> &[(u64, Action)] {
+        &self.events
+    }
+
+    /// The number of actions in the script.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Whether the script has no actions.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// The tick at which the script ends,
+    /// including the full length of any sweep.
+    pub fn duration(&self) -> u64 {
+        self.events
+            .iter()
+            .map(|(tick, action)| match action {
+                Action::Sweep { ticks, .. } => tick + ticks,
+                _ => *tick,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl fmt::Display for Script {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (tick, action) in &self.events {
+            writeln!(f, "{} {}", tick, action)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Script {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut script = Script::new();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default();
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if words.is_empty() {
+                continue;
+            }
+            let error = |message: String| ParseError {
+                line: i + 1,
+                message,
+            };
+            let number = |word: Option<&&str>, what: &str| -> Result<u64, ParseError> {
+                let word = word.ok_or_else(|| error(format!("missing {}", what)))?;
+                word.parse()
+                    .map_err(|_| error(format!("invalid {} `{}`", what, word)))
+            };
+
+            let tick = number(words.first(), "tick")?;
+            let verb = words
+                .get(1)
+                .ok_or_else(|| error("missing action".to_string()))?;
+            let arg = words.get(2).copied();
+            let action = match (*verb, arg) {
+                ("press", Some(name)) | ("release", Some(name)) => {
+                    let press = *verb == "press";
+                    match (Button::from_name(name), stick_from_name(name)) {
+                        (Some(button), _) if press => Action::Press(button),
+                        (Some(button), _) => Action::Release(button),
+                        (None, Some(position)) if press => Action::Stick(position),
+                        (None, Some(_)) => Action::Stick(StickPosition::Center),
+                        (None, None) => return Err(error(format!("unknown button `{}`", name))),
+                    }
+                }
+                ("stick", Some(name)) => Action::Stick(
+                    stick_from_name(name)
+                        .ok_or_else(|| error(format!("unknown stick position `{}`", name)))?,
+                ),
+                ("roll", _) => Action::Roll(number(words.get(2), "roll")?.min(1023) as u16),
+                ("sweep", _) => Action::Sweep {
+                    to: number(words.get(2), "roll")?.min(1023) as u16,
+                    ticks: number(words.get(3), "tick count")?,
+                },
+                ("press", None) | ("release", None) | ("stick", None) => {
+                    return Err(error(format!("missing argument to `{}`", verb)))
+                }
+                _ => return Err(error(format!("unknown action `{}`", verb))),
+            };
+            script = script.at(tick, action);
+        }
+        Ok(script)
+    }
+}
+
+/// An ongoing paddle roll sweep
+#[derive(Debug, Copy, Clone)]
+struct Sweep {
+    start: u64,
+    from: u16,
+    to: u16,
+    ticks: u64,
+}
+
+impl Sweep {
+    fn value_at(&self, tick: u64) -> u16 {
+        let elapsed = tick.saturating_sub(self.start);
+        if elapsed >= self.ticks {
+            return self.to;
+        }
+        let from = i64::from(self.from);
+        let to = i64::from(self.to);
+        (from + (to - from) * elapsed as i64 / self.ticks as i64) as u16
+    }
+}
+
+/// Applies a script to a simulated device over time.
+#[derive(Debug, Clone)]
+pub struct ScriptPlayer {
+    script: Script,
+    /// index of the next action to apply
+    next: usize,
+    sweep: Option<Sweep>,
+}
+
+impl ScriptPlayer {
+    pub fn new(script: Script) -> Self {
+        ScriptPlayer {
+            script,
+            next: 0,
+            sweep: None,
+        }
+    }
+
+    pub fn script(&self) -> &Script {
+        &self.script
+    }
+
+    /// Start the script over.
+    pub fn reset(&mut self) {
+        self.next = 0;
+        self.sweep = None;
+    }
+
+    /// Whether all actions have been applied
+    /// and no sweep is in progress.
+    pub fn is_done(&self) -> bool {
+        self.next >= self.script.events.len() && self.sweep.is_none()
+    }
+
+    /// Apply all actions up to and including the given tick.
+    ///
+    /// Ticks must not go backwards between calls.
+    pub fn update(&mut self, tick: u64, device: &mut SimulatedDevice) {
+        while let Some((t, action)) = self.script.events.get(self.next).copied() {
+            if t > tick {
+                break;
+            }
+            self.next += 1;
+            match action {
+                Action::Sweep { to, ticks } => {
+                    self.sweep = Some(Sweep {
+                        start: t,
+                        from: device.state().roll,
+                        to,
+                        ticks,
+                    });
+                }
+                // a roll interrupts any sweep in progress
+                Action::Roll(_) => {
+                    self.sweep = None;
+                    action.apply(device);
+                }
+                _ => action.apply(device),
+            }
+        }
+
+        if let Some(sweep) = self.sweep {
+            let roll = sweep.value_at(tick);
+            if roll != device.state().roll {
+                device.set_roll(roll);
+            }
+            if tick >= sweep.start + sweep.ticks {
+                self.sweep = None;
+            }
+        }
+    }
+}
@@ -0,0 +1,413 @@
+//! Terminal front-end for the simulated device.
+//!
+//! Requires the `tui` feature and a Unix terminal
+//! with support for ANSI escape codes.
+//! [`run_terminal`] draws the controller with unicode shading
+//! and 24-bit colors, reads the keyboard for its inputs,
+//! and calls the game's update function once per frame,
+//! so games can be played over SSH without a display.
+//!
+//! | Key                          | Controller                   |
+//! |------------------------------|------------------------------|
+//! | arrow keys, W/A/S/D          | stick                        |
+//! | Enter                        | button 1 (primary)           |
+//! | Space                        | button 2 (trigger)           |
+//! | Backspace                    | back button                  |
+//! | M                            | menu button                  |
+//! | F                            | Fuji button                  |
+//! | Q / E (Shift for faster)     | roll the paddle              |
+//! | Ctrl+C                       | quit                         |
+//!
+//! Terminals do not report key releases,
+//! so a key press holds the respective input for a short while,
+//! which is extended while the key repeats.
+//!
+//! ```no_run
+//! use vcs_classic_hid::{Device, LedReport};
+//! use vcs_classic_hid_simulator::tui::run_terminal;
+//!
+//! run_terminal(|device, _ticks| {
+//!     let on = if device.is_button_1_down() { 0xFF } else { 0 };
+//!     device.write(LedReport::filled(on)).is_ok()
+//! })?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+use std::{
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+use vcs_classic_hid::{
+    input::StickPosition,
+    led::mapping::{roll_to_position, ROLL_RANGE},
+    State,
+};
+
+use crate::SimulatedDevice;
+
+/// the time between frames
+const FRAME_TIME: Duration = Duration::from_millis(25);
+/// how long a key press holds an input
+const HOLD_TIME: Duration = Duration::from_millis(400);
+/// how much the paddle rolls per key press
+const ROLL_STEP: i32 = 16;
+/// how much the paddle rolls per key press with Shift
+const ROLL_STEP_FAST: i32 = 64;
+
+const WIDTH: usize = 44;
+const HEIGHT: usize = 19;
+const CENTER_X: f32 = 21.5;
+const CENTER_Y: f32 = 9.;
+const RADIUS_X: f32 = 18.;
+const RADIUS_Y: f32 = 8.;
+
+/// shading characters by increasing intensity
+const SHADES: [char; 4] = ['░', '▒', '▓', '█'];
+
+/// Run a game on a simulated controller in the terminal.
+///
+/// `update` is called once per frame
+/// with the simulated device and the current frame number,
+/// and should return `false` once the game has ended.
+/// The terminal is restored when the game ends or Ctrl+C is pressed.
+pub fn run_terminal<F>(mut update: F) -> io::Result<()>
+where
+    F: FnMut(&mut SimulatedDevice, u64) -> bool,
+{
+    let _terminal = RawTerminal::enter()?;
+    let mut device = SimulatedDevice::new();
+    #[cfg(all(feature = "gamepad", target_os = "linux"))]
+    let mut gamepad = crate::gamepad::Gamepad::from_env();
+    let mut keys = Keys::default();
+    let mut f: u64 = 0;
+    let mut last_frame = Instant::now();
+
+    loop {
+        // -- input processing from the keyboard --
+        let now = Instant::now();
+        let mut roll = i32::from(device.state().roll);
+        for key in read_keys()? {
+            match key {
+                Key::Quit => return Ok(()),
+                Key::Roll(delta) => roll += delta,
+                Key::Hold(control) => keys.press(control, now),
+            }
+        }
+        let state = State {
+            roll: roll.rem_euclid(i32::from(ROLL_RANGE)) as u16,
+            ..keys.state(now)
+        };
+        #[cfg(all(feature = "gamepad", target_os = "linux"))]
+        let state = crate::gamepad::merge_input(&mut gamepad, state);
+        // only send an input report when something changed
+        if state != device.state() {
+            device.push_state(state);
+        }
+
+        // -- game logic --
+        device.advance(now - last_frame);
+        last_frame = now;
+        if !update(&mut device, f) {
+            return Ok(());
+        }
+
+        // -- rendering --
+        draw_device(&device, f)?;
+
+        f += 1;
+        std::thread::sleep(FRAME_TIME.saturating_sub(now.elapsed()));
+    }
+}
+
+/// The inputs which are held by a key press.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Control {
+    Up,
+    Right,
+    Down,
+    Left,
+    Button1,
+    Button2,
+    Back,
+    Menu,
+    Fuji,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Key {
+    Hold(Control),
+    Roll(i32),
+    Quit,
+}
+
+/// The time until which each control is held.
+#[derive(Debug, Default)]
+struct Keys {
+    held_until: [Option<Instant>; 9],
+}
+
+impl Keys {
+    fn press(&mut self, control: Control, now: Instant) {
+        self.held_until[control as usize] = Some(now + HOLD_TIME);
+    }
+
+    fn is_down(&self, control: Control, now: Instant) -> bool {
+        matches!(self.held_until[control as usize], Some(until) if until > now)
+    }
+
+    /// The controller state from the keys held,
+    /// with the paddle at 0.
+    fn state(&self, now: Instant) -> State {
+        let up = self.is_down(Control::Up, now);
+        let right = self.is_down(Control::Right, now);
+        let down = self.is_down(Control::Down, now);
+        let left = self.is_down(Control::Left, now);
+        let stick_position = StickPosition::from_directions(up, right, down, left);
+
+        State {
+            stick_position,
+            button_1: self.is_down(Control::Button1, now),
+            button_2: self.is_down(Control::Button2, now),
+            button_back: self.is_down(Control::Back, now),
+            button_menu: self.is_down(Control::Menu, now),
+            button_fuji: self.is_down(Control::Fuji, now),
+            roll: 0,
+        }
+    }
+}
+
+/// Read all pending key presses from the terminal without blocking.
+fn read_keys() -> io::Result<Vec<Key>> {
+    let mut buf = [0; 64];
+    let len = unsafe { libc::read(libc::STDIN_FILENO, buf.as_mut_ptr() as *mut _, buf.len()) };
+    if len < 0 {
+        let err = io::Error::last_os_error();
+        return match err.kind() {
+            io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted => Ok(Vec::new()),
+            _ => Err(err),
+        };
+    }
+
+    let mut bytes = buf[..len as usize].iter().copied();
+    let mut keys = Vec::new();
+    while let Some(b) = bytes.next() {
+        let key = match b {
+            // escape sequences for the arrow keys
+            0x1B => {
+                if bytes.next() != Some(b'[') {
+                    continue;
+                }
+                match bytes.next() {
+                    Some(b'A') => Key::Hold(Control::Up),
+                    Some(b'B') => Key::Hold(Control::Down),
+                    Some(b'C') => Key::Hold(Control::Right),
+                    Some(b'D') => Key::Hold(Control::Left),
+                    _ => continue,
+                }
+            }
+            b'w' | b'W' => Key::Hold(Control::Up),
+            b's' | b'S' => Key::Hold(Control::Down),
+            b'd' | b'D' => Key::Hold(Control::Right),
+            b'a' | b'A' => Key::Hold(Control::Left),
+            b'\r' | b'\n' => Key::Hold(Control::Button1),
+            b' ' => Key::Hold(Control::Button2),
+            0x7F | 0x08 => Key::Hold(Control::Back),
+            b'm' | b'M' => Key::Hold(Control::Menu),
+            b'f' | b'F' => Key::Hold(Control::Fuji),
+            b'q' => Key::Roll(-ROLL_STEP),
+            b'e' => Key::Roll(ROLL_STEP),
+            b'Q' => Key::Roll(-ROLL_STEP_FAST),
+            b'E' => Key::Roll(ROLL_STEP_FAST),
+            // Ctrl+C
+            0x03 => Key::Quit,
+            _ => continue,
+        };
+        keys.push(key);
+    }
+    Ok(keys)
+}
+
+/// Guard for the terminal in raw mode,
+/// restoring its original settings when dropped.
+struct RawTerminal {
+    original: libc::termios,
+}
+
+impl RawTerminal {
+    fn enter() -> io::Result<Self> {
+        let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut termios) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let original = termios;
+
+        // no line buffering, echo or signals, and reads never block
+        termios.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG | libc::IEXTEN);
+        termios.c_iflag &= !(libc::IXON | libc::ICRNL);
+        termios.c_cc[libc::VMIN] = 0;
+        termios.c_cc[libc::VTIME] = 0;
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // alternate screen, hidden cursor
+        let mut stdout = io::stdout();
+        stdout.write_all(b"\x1b[?1049h\x1b[?25l\x1b[2J")?;
+        stdout.flush()?;
+        Ok(RawTerminal { original })
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let mut stdout = io::stdout();
+        let _ = stdout.write_all(b"\x1b[0m\x1b[?25h\x1b[?1049l");
+        let _ = stdout.flush();
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original);
+        }
+    }
+}
+
+/// A character in the screen with an optional 24-bit color.
+#[derive(Debug, Copy, Clone)]
+struct Cell {
+    c: char,
+    color: Option<(u8, u8, u8)>,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell { c: ' ', color: None }
+    }
+}
+
+/// The character for an LED at the given intensity.
+fn led_cell(value: u8, color: (f32, f32, f32)) -> Cell {
+    if value == 0 {
+        return Cell { c: '·', color: None };
+    }
+    let v = f32::from(value);
+    Cell {
+        c: SHADES[usize::from(value) * SHADES.len() / 256],
+        color: Some((
+            (v * color.0) as u8,
+            (v * color.1) as u8,
+            (v * color.2) as u8,
+        )),
+    }
+}
+
+/// The cell in the ring at the given position and radius,
+/// with LED 0 at the bottom and going clockwise.
+fn ring_cell(position: f32, radius_x: f32, radius_y: f32, shake: f32) -> (usize, usize) {
+    let angle = position * std::f32::consts::TAU / 24.;
+    let x = CENTER_X + shake - radius_x * angle.sin();
+    let y = CENTER_Y + radius_y * angle.cos();
+    (x.round() as usize, y.round() as usize)
+}
+
+/// Draw the simulated classic controller on the terminal.
+fn draw_device(device: &SimulatedDevice, f: u64) -> io::Result<()> {
+    let mut screen = [[Cell::default(); WIDTH]; HEIGHT];
+
+    // jitter left and right while vibrating
+    let rumble = device.current_intensity();
+    let shake = if rumble > 0 && f.is_multiple_of(2) { 1. } else { 0. };
+
+    for (i, led) in device.leds().iter().copied().enumerate() {
+        let (x, y) = ring_cell(i as f32, RADIUS_X, RADIUS_Y, shake);
+        screen[y][x] = led_cell(led, (1., 0.25, 0.));
+    }
+
+    // paddle roll marker, just outside the ring
+    let roll = device.state().roll;
+    let (x, y) = ring_cell(roll_to_position(roll), RADIUS_X + 2.5, RADIUS_Y + 1., shake);
+    if let Some(cell) = screen.get_mut(y).and_then(|row| row.get_mut(x)) {
+        *cell = Cell {
+            c: '◆',
+            color: Some((0x87, 0xCE, 0xEB)),
+        };
+    }
+
+    // stick, in the center
+    let (dx, dy) = match device.stick_position() {
+        StickPosition::Center => (0, 0),
+        StickPosition::Up => (0, -1),
+        StickPosition::UpRight => (1, -1),
+        StickPosition::Right => (1, 0),
+        StickPosition::DownRight => (1, 1),
+        StickPosition::Down => (0, 1),
+        StickPosition::DownLeft => (-1, 1),
+        StickPosition::Left => (-1, 0),
+        StickPosition::UpLeft => (-1, -1),
+    };
+    let (stick_x, stick_y) = ((CENTER_X + shake) as i32, CENTER_Y as i32);
+    for y in -1..=1 {
+        for x in -1..=1 {
+            let c = if (x, y) == (dx, dy) { '●' } else { '·' };
+            screen[(stick_y + y) as usize][(stick_x + 2 * x) as usize] = Cell { c, color: None };
+        }
+    }
+
+    let mut out = String::new();
+    out.push_str("\x1b[H");
+    out.push_str(&format!(
+        "Frame: {:6}  time: {:6.2}\x1b[K\r\n",
+        f,
+        device.now().as_secs_f32()
+    ));
+    for row in screen.iter() {
+        for cell in row.iter() {
+            match cell.color {
+                Some((r, g, b)) => {
+                    out.push_str(&format!("\x1b[38;2;{};{};{}m{}\x1b[0m", r, g, b, cell.c))
+                }
+                None => out.push(cell.c),
+            }
+        }
+        out.push_str("\x1b[K\r\n");
+    }
+
+    // Fuji LED, centered below the ring
+    let fuji = led_cell(device.fuji_led(), (1., 0.36, 0.25));
+    out.push_str(&" ".repeat(CENTER_X as usize - 2));
+    out.push_str("Fuji ");
+    match fuji.color {
+        Some((r, g, b)) => out.push_str(&format!("\x1b[38;2;{};{};{}m{}\x1b[0m", r, g, b, fuji.c)),
+        None => out.push(fuji.c),
+    }
+    out.push_str("\x1b[K\r\n\r\n");
+
+    // buttons, in reverse video while pressed
+    let buttons = [
+        ("1", device.is_button_1_down()),
+        ("2", device.is_button_2_down()),
+        ("back", device.is_button_back_down()),
+        ("menu", device.is_button_menu_down()),
+        ("fuji", device.is_button_fuji_down()),
+    ];
+    for (label, down) in buttons.iter() {
+        if *down {
+            out.push_str(&format!("\x1b[7m[{}]\x1b[0m ", label));
+        } else {
+            out.push_str(&format!("[{}] ", label));
+        }
+    }
+    out.push_str("\x1b[K\r\n");
+
+    let bar = usize::from(rumble) * 10 / 255;
+    out.push_str(&format!(
+        "roll: {:4}  rumble: [{}{}]\x1b[K\r\n",
+        roll,
+        "█".repeat(bar),
+        " ".repeat(10 - bar)
+    ));
+    out.push_str(
+        "Arrows/WASD: stick  Enter/Space: 1/2  Backspace: back  M: menu  F: Fuji  Q/E: roll  Ctrl+C: quit\x1b[K",
+    );
+
+    let mut stdout = io::stdout();
+    stdout.write_all(out.as_bytes())?;
+    stdout.flush()
+}
@@ -0,0 +1,123 @@
+//! Replays recorded gamepad and keyboard input onto the simulated device.
+use std::time::Duration;
+
+use vcs_classic_hid::{State, StickPosition};
+use vcs_classic_hid_simulator::{
+    gamepad::{codes, Bridge, InputEvent, Mapping, Recording, Replay},
+    SimulatedDevice,
+};
+
+/// The time between two frames
+const FRAME: Duration = Duration::from_millis(25);
+
+fn fixture(name: &str) -> String {
+    let path = format!("{}/tests/data/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path, e))
+}
+
+/// Replay a recording frame by frame,
+/// returning the input state of the device at each frame.
+fn replay(recording: Recording, mapping: Mapping) -> Vec<State> {
+    let mut device = SimulatedDevice::new();
+    let mut bridge = Bridge::new(mapping);
+    let mut replay = Replay::new(recording);
+    let mut states = Vec::new();
+    let mut elapsed = Duration::default();
+    while !replay.is_done() {
+        replay.update(elapsed, &mut bridge);
+        bridge.apply(&mut device);
+        states.push(device.state());
+        elapsed += FRAME;
+    }
+    states
+}
+
+#[test]
+fn xbox_pad() {
+    let recording = Recording::parse_evtest(&fixture("xbox.evtest")).unwrap();
+    assert!(recording.ranges.contains(&(codes::ABS_X, -32768, 32767)));
+    assert!(recording.ranges.contains(&(codes::ABS_RZ, 0, 255)));
+    assert_eq!(recording.duration(), Duration::from_millis(700));
+
+    let states = replay(recording, Mapping::default());
+    assert_eq!(states.len(), 29);
+
+    // A
+    assert!(states[0].button_1);
+    assert!(states[3].button_1);
+    assert_eq!(states[4], State::default());
+    // D-pad up
+    assert_eq!(states[8].stick_position, StickPosition::Up);
+    // left stick down and right
+    assert_eq!(states[12].stick_position, StickPosition::DownRight);
+    // within the dead zone
+    assert_eq!(states[16].stick_position, StickPosition::Center);
+    // right trigger, then left shoulder button
+    let rolls: Vec<u16> = states[16..24].iter().map(|s| s.roll).collect();
+    assert_eq!(rolls, [16, 32, 48, 64, 48, 32, 32, 32]);
+    // Xbox and Start buttons
+    assert!(states[24].button_fuji && states[24].button_menu);
+    assert!(!states[24].button_1 && !states[24].button_back);
+    assert_eq!(
+        states[28],
+        State {
+            roll: 32,
+            ..State::default()
+        }
+    );
+}
+
+#[test]
+fn keyboard_mapping() {
+    let mapping: Mapping = fixture("keyboard.map").parse().unwrap();
+    assert_eq!(mapping.bindings().len(), 11);
+
+    let key = |code, value| InputEvent::new(codes::EV_KEY, code, value);
+    let (w, d, space, tab, left) = (17, 32, 57, 15, 105);
+    let mut device = SimulatedDevice::new();
+    let mut bridge = Bridge::new(mapping);
+
+    for event in &[key(w, 1), key(d, 1), key(space, 1), key(left, 1)] {
+        bridge.handle(event);
+    }
+    bridge.apply(&mut device);
+    let state = device.state();
+    assert_eq!(state.stick_position, StickPosition::UpRight);
+    assert!(state.button_1 && !state.button_2);
+    assert_eq!(state.roll, 1016);
+
+    for event in &[key(w, 0), key(space, 0), key(left, 0), key(tab, 1)] {
+        bridge.handle(event);
+    }
+    bridge.apply(&mut device);
+    let state = device.state();
+    assert_eq!(state.stick_position, StickPosition::Right);
+    assert!(!state.button_1 && state.button_menu);
+    assert_eq!(state.roll, 1016);
+}
+
+#[test]
+fn invalid_mapping() {
+    let error = "KEY_SPACE 1\nKEY_UP stick-x\n"
+        .parse::<Mapping>()
+        .unwrap_err();
+    assert_eq!(error.line, 2);
+
+    let error = "ABS_X roll 0\n".parse::<Mapping>().unwrap_err();
+    assert_eq!(error.line, 1);
+    assert!("BTN_NOPE 1".parse::<Mapping>().is_err());
+}
+
+#[test]
+fn full_axis_range() {
+    let mapping: Mapping =
+        "ABS_X stick-x -2147483648 2147483647\nABS_Z roll 2147483647 -2147483648"
+            .parse()
+            .unwrap();
+    let mut bridge = Bridge::new(mapping);
+    bridge.handle(&InputEvent::new(codes::EV_ABS, codes::ABS_X, i32::MIN));
+    bridge.handle(&InputEvent::new(codes::EV_ABS, codes::ABS_Z, i32::MIN));
+    let state = bridge.state();
+    assert_eq!(state.stick_position, StickPosition::Left);
+    assert_eq!(state.roll, 1023);
+}
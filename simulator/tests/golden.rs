@@ -0,0 +1,53 @@
+//! Snapshot tests of the built-in LED animations.
+//!
+//! Run with `UPDATE_GOLDEN=1` after an intended change
+//! to regenerate the files in `tests/golden`.
+use vcs_classic_hid::led::{
+    anims::{Chase, Comet, Fire, Pulsate, Sparkle, Spinner},
+    LedAnimation, LedSelection,
+};
+use vcs_classic_hid_simulator::{capture::record, golden::assert_golden};
+
+fn check<A: LedAnimation>(name: &str, animation: A, ticks: u64) {
+    let frames = record(animation, 0..ticks);
+    let path = format!("{}/tests/golden/{}.txt", env!("CARGO_MANIFEST_DIR"), name);
+    assert_golden(path, &frames);
+}
+
+#[test]
+fn comet() {
+    check("comet", Comet::new(), 64);
+}
+
+#[test]
+fn chase() {
+    check("chase", Chase::new(), 64);
+}
+
+#[test]
+fn spinner() {
+    check("spinner", Spinner::new(), 64);
+}
+
+#[test]
+fn pulsate() {
+    check("pulsate", Pulsate::new(), 64);
+}
+
+#[test]
+fn sparkle() {
+    check(
+        "sparkle",
+        Sparkle::new_with_params(LedSelection::ALL, 16, 0xFF, 0x20, 42),
+        48,
+    );
+}
+
+#[test]
+fn fire() {
+    check(
+        "fire",
+        Fire::new_with_params(LedSelection::ALL, 0x40, 0xC0, 7),
+        48,
+    );
+}
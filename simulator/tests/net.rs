@@ -0,0 +1,211 @@
+//! The network protocol of the simulated device, as seen by a client.
+use std::{
+    io::{BufRead, BufReader, Lines, Write},
+    net::TcpStream,
+};
+
+use vcs_classic_hid::Device;
+use vcs_classic_hid_simulator::net::{Server, SharedDevice};
+
+/// A client connected over TCP
+struct Client {
+    stream: TcpStream,
+    lines: Lines<BufReader<TcpStream>>,
+}
+
+impl Client {
+    fn connect(server: &Server) -> Self {
+        let stream = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+        let lines = BufReader::new(stream.try_clone().unwrap()).lines();
+        Client { stream, lines }
+    }
+
+    /// Send a request and return the next line received.
+    fn request(&mut self, line: &str) -> String {
+        writeln!(self.stream, "{}", line).unwrap();
+        self.next_line()
+    }
+
+    fn next_line(&mut self) -> String {
+        self.lines.next().unwrap().unwrap()
+    }
+}
+
+fn serve() -> (SharedDevice, Server) {
+    let device = SharedDevice::default();
+    let server = Server::bind_tcp("127.0.0.1:0", device.clone()).unwrap();
+    (device, server)
+}
+
+#[test]
+fn string_escapes() {
+    let (_device, server) = serve();
+    let mut client = Client::connect(&server);
+
+    let response = client.request(r#"{"id": "a\"b\\c\/\n\té😀", "cmd": "unplug"}"#);
+    assert_eq!(
+        response,
+        "{\"id\":\"a\\\"b\\\\c/\\n\\t\u{e9}\u{1F600}\",\"ok\":true}"
+    );
+
+    // control characters are escaped on the way out
+    let response = client.request(r#"{"id": "\u0001", "cmd": "replug"}"#);
+    assert_eq!(response, r#"{"id":"\u0001","ok":true}"#);
+}
+
+#[test]
+fn malformed_json() {
+    let (_device, server) = serve();
+    let mut client = Client::connect(&server);
+
+    let malformed = [
+        r#"{"cmd": "state""#,
+        r#"{"cmd" "state"}"#,
+        r#"{"cmd": "state",}"#,
+        r#"{cmd: "state"}"#,
+        r#"{"cmd": "state"} {"#,
+        r#"[1, 2,]"#,
+        r#"tru"#,
+        r#"-"#,
+        r#""unterminated"#,
+        r#""\x""#,
+        // signs are not hexadecimal digits
+        r#""\u+041""#,
+        // unpaired surrogates
+        r#""\ud83d""#,
+        r#""\ud83dA""#,
+        r#""\ude00""#,
+    ];
+    for line in &malformed {
+        let response = client.request(line);
+        assert!(
+            response.starts_with(r#"{"ok":false,"error":"invalid JSON: "#),
+            "{} -> {}",
+            line,
+            response
+        );
+    }
+
+    // too deeply nested
+    let deep = format!("{}{}", "[".repeat(40), "]".repeat(40));
+    let response = client.request(&deep);
+    assert!(response.contains("too deeply nested"), "{}", response);
+    // but well within the limit is fine
+    let nested = format!("{}{}", "[".repeat(16), "]".repeat(16));
+    assert_eq!(
+        client.request(&nested),
+        r#"{"ok":false,"error":"missing `cmd`"}"#
+    );
+}
+
+#[test]
+fn error_responses() {
+    let (device, server) = serve();
+    let mut client = Client::connect(&server);
+
+    let cases = [
+        (
+            r#"{"id": 1}"#,
+            r#"{"id":1,"ok":false,"error":"missing `cmd`"}"#,
+        ),
+        (
+            r#"{"id": 2, "cmd": "dance"}"#,
+            r#"{"id":2,"ok":false,"error":"unknown command `dance`"}"#,
+        ),
+        (
+            r#"{"cmd": "push"}"#,
+            r#"{"ok":false,"error":"missing `state`"}"#,
+        ),
+        (
+            r#"{"cmd": "push", "state": [1]}"#,
+            r#"{"ok":false,"error":"`state` must be an object"}"#,
+        ),
+        (
+            r#"{"cmd": "push", "state": {"roll": 1024}}"#,
+            r#"{"ok":false,"error":"invalid value for `roll`"}"#,
+        ),
+        (
+            r#"{"cmd": "push", "state": {"stick_position": "sideways"}}"#,
+            r#"{"ok":false,"error":"invalid value for `stick_position`"}"#,
+        ),
+        (
+            r#"{"cmd": "push", "state": {"button_3": true}}"#,
+            r#"{"ok":false,"error":"unknown state member `button_3`"}"#,
+        ),
+        (
+            r#"{"cmd": "advance", "ms": -5}"#,
+            r#"{"ok":false,"error":"missing or invalid `ms`"}"#,
+        ),
+    ];
+    for (request, expected) in &cases {
+        assert_eq!(&client.request(request), expected);
+    }
+
+    // nothing was pushed
+    let mut device = device.clone();
+    let mut buf = [0; 8];
+    assert_eq!(device.read(&mut buf), Ok(0));
+}
+
+#[test]
+fn unsubscribe() {
+    let (device, server) = serve();
+    let mut client = Client::connect(&server);
+    let mut app_device = device.clone();
+
+    assert_eq!(client.request(r#"{"cmd": "subscribe"}"#), r#"{"ok":true}"#);
+    app_device.write([2, 1, 0xFF]).unwrap();
+    assert_eq!(
+        client.next_line(),
+        r#"{"event":"write","time_ms":0,"data":[2,1,255]}"#
+    );
+
+    assert_eq!(
+        client.request(r#"{"cmd": "unsubscribe"}"#),
+        r#"{"ok":true}"#
+    );
+    app_device.write([2, 1, 0x80]).unwrap();
+    // the next line is the response, not an event
+    assert_eq!(
+        client.request(r#"{"cmd": "advance", "ms": 20}"#),
+        r#"{"ok":true,"time_ms":20}"#
+    );
+
+    // unsubscribing again is harmless
+    assert_eq!(
+        client.request(r#"{"cmd": "unsubscribe"}"#),
+        r#"{"ok":true}"#
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn unix_socket() {
+    use std::os::unix::net::UnixStream;
+
+    let path = std::env::temp_dir().join(format!(
+        "vcs-classic-hid-simulator-test-{}.sock",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    let device = SharedDevice::default();
+    let server = Server::bind_unix(&path, device.clone()).unwrap();
+    assert_eq!(server.socket_path(), Some(path.as_path()));
+    assert_eq!(server.local_addr(), None);
+
+    let mut stream = UnixStream::connect(&path).unwrap();
+    let mut lines = BufReader::new(stream.try_clone().unwrap()).lines();
+    writeln!(
+        stream,
+        r#"{{"id": "x", "cmd": "push", "state": {{"button_1": true, "roll": 512}}}}"#
+    )
+    .unwrap();
+    assert_eq!(lines.next().unwrap().unwrap(), r#"{"id":"x","ok":true}"#);
+    let state = device.lock().state();
+    assert!(state.button_1);
+    assert_eq!(state.roll, 512);
+
+    // the socket file is removed once stopped
+    server.stop();
+    assert!(!path.exists());
+}
@@ -0,0 +1,66 @@
+//! HID report descriptor of the virtual controller.
+//!
+//! It describes the same reports as understood by `vcs-classic-hid`:
+//!
+//! - input report #1 (5 bytes):
+//!   buttons 1 and 2, then back, menu and Fuji buttons
+//!   and the stick as a hat switch, then the paddle roll (10 bits);
+//! - output report #1 (6 bytes): force feedback;
+//! - output report #2 (28 bytes): LEDs.
+
+#[rustfmt::skip]
+pub const REPORT_DESCRIPTOR: &[u8] = &[
+    0x05, 0x01,         // Usage Page (Generic Desktop)
+    0x09, 0x04,         // Usage (Joystick)
+    0xA1, 0x01,         // Collection (Application)
+    0x85, 0x01,         //   Report ID (1)
+    // buttons 1 and 2
+    0x05, 0x09,         //   Usage Page (Button)
+    0x19, 0x01,         //   Usage Minimum (1)
+    0x29, 0x02,         //   Usage Maximum (2)
+    0x15, 0x00,         //   Logical Minimum (0)
+    0x25, 0x01,         //   Logical Maximum (1)
+    0x75, 0x01,         //   Report Size (1)
+    0x95, 0x02,         //   Report Count (2)
+    0x81, 0x02,         //   Input (Data, Variable, Absolute)
+    0x95, 0x06,         //   Report Count (6)
+    0x81, 0x01,         //   Input (Constant)
+    // back, menu and Fuji buttons
+    0x19, 0x03,         //   Usage Minimum (3)
+    0x29, 0x05,         //   Usage Maximum (5)
+    0x95, 0x03,         //   Report Count (3)
+    0x81, 0x02,         //   Input (Data, Variable, Absolute)
+    0x95, 0x01,         //   Report Count (1)
+    0x81, 0x01,         //   Input (Constant)
+    // stick, 0 is the center
+    0x05, 0x01,         //   Usage Page (Generic Desktop)
+    0x09, 0x39,         //   Usage (Hat Switch)
+    0x15, 0x01,         //   Logical Minimum (1)
+    0x25, 0x08,         //   Logical Maximum (8)
+    0x35, 0x00,         //   Physical Minimum (0)
+    0x46, 0x3B, 0x01,   //   Physical Maximum (315)
+    0x65, 0x14,         //   Unit (Degrees)
+    0x75, 0x04,         //   Report Size (4)
+    0x81, 0x42,         //   Input (Data, Variable, Absolute, Null State)
+    0x65, 0x00,         //   Unit (None)
+    0x45, 0x00,         //   Physical Maximum (0)
+    // paddle roll
+    0x09, 0x37,         //   Usage (Dial)
+    0x15, 0x00,         //   Logical Minimum (0)
+    0x26, 0xFF, 0x03,   //   Logical Maximum (1023)
+    0x75, 0x10,         //   Report Size (16)
+    0x81, 0x02,         //   Input (Data, Variable, Absolute)
+    // force feedback
+    0x06, 0x00, 0xFF,   //   Usage Page (Vendor Defined)
+    0x09, 0x01,         //   Usage (1)
+    0x26, 0xFF, 0x00,   //   Logical Maximum (255)
+    0x75, 0x08,         //   Report Size (8)
+    0x95, 0x05,         //   Report Count (5)
+    0x91, 0x02,         //   Output (Data, Variable, Absolute)
+    // LEDs
+    0x85, 0x02,         //   Report ID (2)
+    0x09, 0x02,         //   Usage (2)
+    0x95, 0x1B,         //   Report Count (27)
+    0x91, 0x02,         //   Output (Data, Variable, Absolute)
+    0xC0,               // End Collection
+];
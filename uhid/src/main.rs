@@ -0,0 +1,172 @@
+//! Virtual VCS classic controller.
+//!
+//! Creates a virtual HID device through the Linux uhid driver
+//! with the same vendor and product IDs as the classic controller,
+//! so that any program using hidraw or `vcs_classic_hid::open()`
+//! will find it as if it were the real device.
+//!
+//! Input is driven by a [`SimulatedDevice`],
+//! optionally following a script in the simulator's text format.
+//! LED and force feedback reports sent to the device are printed out.
+
+#[cfg(target_os = "linux")]
+mod descriptor;
+#[cfg(target_os = "linux")]
+mod uhid;
+
+#[cfg(target_os = "linux")]
+fn main() -> Result<(), anyhow::Error> {
+    linux::main()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn main() {
+    eprintln!("The virtual controller requires Linux with uhid support");
+    std::process::exit(1);
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::{
+        path::PathBuf,
+        time::{Duration, Instant},
+    };
+
+    use anyhow::{self, Context};
+    use clap::Parser;
+    use vcs_classic_hid::{Device, FfReport, PRODUCT_ID, VENDOR_ID};
+    use vcs_classic_hid_simulator::{
+        script::{Script, ScriptPlayer},
+        SimulatedDevice,
+    };
+
+    use crate::{
+        descriptor::REPORT_DESCRIPTOR,
+        uhid::{Event, Uhid},
+    };
+
+    const DEVICE_NAME: &str = "Atari Classic Controller (virtual)";
+
+    #[derive(Debug, Parser)]
+    struct App {
+        /// A script of inputs to play, in the simulator's text format
+        script: Option<PathBuf>,
+        /// The duration of each script tick, in milliseconds
+        #[clap(long, default_value_t = 25)]
+        tick_ms: u64,
+        /// Destroy the device and exit once the script has been played
+        #[clap(long)]
+        exit: bool,
+    }
+
+    pub fn main() -> Result<(), anyhow::Error> {
+        let opt = App::parse();
+
+        let script = match &opt.script {
+            Some(path) => std::fs::read_to_string(path)
+                .with_context(|| format!("Could not read {}", path.display()))?
+                .parse::<Script>()
+                .with_context(|| format!("Invalid script {}", path.display()))?,
+            None => Script::new(),
+        };
+
+        let mut uhid = Uhid::create(DEVICE_NAME, VENDOR_ID, PRODUCT_ID, REPORT_DESCRIPTOR)
+            .context("Could not create virtual device (is the uhid module loaded, and do you have access to /dev/uhid?)")?;
+        eprintln!("Created virtual device \"{}\"", DEVICE_NAME);
+
+        let mut device = SimulatedDevice::new();
+        let mut player = ScriptPlayer::new(script);
+        let tick_len = Duration::from_millis(opt.tick_ms.max(1));
+        let start = Instant::now();
+        let mut next_tick = start;
+        let mut tick: u64 = 0;
+
+        loop {
+            // handle requests from the kernel until the next tick
+            next_tick += tick_len;
+            loop {
+                let now = Instant::now();
+                if now >= next_tick {
+                    break;
+                }
+                if uhid.poll(next_tick - now)? {
+                    let event = uhid.read_event()?;
+                    handle_event(event, &mut uhid, &mut device, start)?;
+                }
+            }
+
+            tick += 1;
+            device.advance(tick_len);
+            player.update(tick, &mut device);
+
+            // forward input reports
+            let mut buf = [0; 6];
+            loop {
+                let len = device.read(&mut buf).map_err(anyhow::Error::msg)?;
+                if len == 0 {
+                    break;
+                }
+                uhid.input(&buf[..len])?;
+            }
+
+            if opt.exit && player.is_done() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_event(
+        event: Event,
+        uhid: &mut Uhid,
+        device: &mut SimulatedDevice,
+        start: Instant,
+    ) -> Result<(), anyhow::Error> {
+        match event {
+            Event::Start => eprintln!("Device started"),
+            Event::Stop => eprintln!("Device stopped"),
+            Event::Open => eprintln!("Device opened"),
+            Event::Close => eprintln!("Device closed"),
+            Event::Output { data } => {
+                print_output(&data, start);
+                device.write(&data).map_err(anyhow::Error::msg)?;
+            }
+            Event::GetReport { id, report_number } => {
+                if report_number == 1 {
+                    uhid.get_report_reply(id, 0, &device.input_report())?;
+                } else {
+                    uhid.get_report_reply(id, libc::EIO as u16, &[])?;
+                }
+            }
+            Event::SetReport { id, data, .. } => {
+                print_output(&data, start);
+                device.write(&data).map_err(anyhow::Error::msg)?;
+                uhid.set_report_reply(id, 0)?;
+            }
+            Event::Other(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Print an output report sent to the device.
+    fn print_output(data: &[u8], start: Instant) {
+        let time = start.elapsed().as_secs_f32();
+        match data {
+            [1, ..] => match FfReport::from_bytes(data) {
+                Some(ff) => println!(
+                    "{:9.3} FF  intensity {:3}, up {:3}, down {:3}, times {:3}",
+                    time,
+                    ff.intensity(),
+                    ff.up_time(),
+                    ff.down_time(),
+                    ff.times(),
+                ),
+                None => println!("{:9.3} FF  {:02X?}", time, data),
+            },
+            [2, 0, ..] => println!("{:9.3} LED reset", time),
+            [2, _, leds @ ..] => println!("{:9.3} LED {:02X?}", time, leds),
+            _ => println!("{:9.3} ??? {:02X?}", time, data),
+        }
+    }
+}
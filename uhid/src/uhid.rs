@@ -0,0 +1,217 @@
+//! Minimal interface to the Linux user-space HID driver (`/dev/uhid`).
+//!
+//! Events are laid out as in `linux/uhid.h`:
+//! a 32-bit event type followed by a packed union of requests,
+//! which are encoded and decoded by hand here.
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Write},
+    os::unix::{fs::OpenOptionsExt, io::AsRawFd},
+    time::Duration,
+};
+
+/// The path to the uhid character device
+pub const UHID_PATH: &str = "/dev/uhid";
+
+/// The size of a `struct uhid_event`
+const EVENT_SIZE: usize = 4 + 4372;
+/// The maximum size of a report or report descriptor
+const DATA_MAX: usize = 4096;
+
+const UHID_DESTROY: u32 = 1;
+const UHID_START: u32 = 2;
+const UHID_STOP: u32 = 3;
+const UHID_OPEN: u32 = 4;
+const UHID_CLOSE: u32 = 5;
+const UHID_OUTPUT: u32 = 6;
+const UHID_GET_REPORT: u32 = 9;
+const UHID_GET_REPORT_REPLY: u32 = 10;
+const UHID_CREATE2: u32 = 11;
+const UHID_INPUT2: u32 = 12;
+const UHID_SET_REPORT: u32 = 13;
+const UHID_SET_REPORT_REPLY: u32 = 14;
+
+const BUS_USB: u16 = 0x03;
+
+/// An event sent by the kernel to the virtual device.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// The HID driver was bound to the device
+    Start,
+    /// The HID driver was unbound from the device
+    Stop,
+    /// The device was opened by some process (e.g. via hidraw)
+    Open,
+    /// The last process using the device closed it
+    Close,
+    /// An output report was written to the device
+    Output { data: Vec<u8> },
+    /// A report was requested from the device,
+    /// to be answered with [`Uhid::get_report_reply`]
+    GetReport { id: u32, report_number: u8 },
+    /// A report was sent to the device,
+    /// to be answered with [`Uhid::set_report_reply`]
+    SetReport {
+        id: u32,
+        report_number: u8,
+        data: Vec<u8>,
+    },
+    /// Any other event
+    Other(u32),
+}
+
+/// A virtual HID device created through uhid.
+///
+/// The device is destroyed when this value is dropped.
+#[derive(Debug)]
+pub struct Uhid {
+    file: File,
+}
+
+impl Uhid {
+    /// Create a new virtual USB HID device.
+    pub fn create(name: &str, vendor: u16, product: u16, descriptor: &[u8]) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_CLOEXEC)
+            .open(UHID_PATH)?;
+        let mut uhid = Uhid { file };
+
+        let mut event = new_event(UHID_CREATE2);
+        let name = name.as_bytes();
+        // leave room for the null terminator
+        let name_len = name.len().min(127);
+        event[4..4 + name_len].copy_from_slice(&name[..name_len]);
+        event[260..262].copy_from_slice(&(descriptor.len() as u16).to_ne_bytes());
+        event[262..264].copy_from_slice(&BUS_USB.to_ne_bytes());
+        event[264..268].copy_from_slice(&u32::from(vendor).to_ne_bytes());
+        event[268..272].copy_from_slice(&u32::from(product).to_ne_bytes());
+        put_data(&mut event[280..], descriptor)?;
+        uhid.send(&event)?;
+        Ok(uhid)
+    }
+
+    /// Deliver an input report, starting with the report ID.
+    pub fn input(&mut self, data: &[u8]) -> io::Result<()> {
+        let mut event = new_event(UHID_INPUT2);
+        event[4..6].copy_from_slice(&(data.len() as u16).to_ne_bytes());
+        put_data(&mut event[6..], data)?;
+        self.send(&event)
+    }
+
+    /// Answer a [`GetReport`](Event::GetReport) request.
+    ///
+    /// `err` is 0 on success or an errno value otherwise.
+    pub fn get_report_reply(&mut self, id: u32, err: u16, data: &[u8]) -> io::Result<()> {
+        let mut event = new_event(UHID_GET_REPORT_REPLY);
+        event[4..8].copy_from_slice(&id.to_ne_bytes());
+        event[8..10].copy_from_slice(&err.to_ne_bytes());
+        event[10..12].copy_from_slice(&(data.len() as u16).to_ne_bytes());
+        put_data(&mut event[12..], data)?;
+        self.send(&event)
+    }
+
+    /// Answer a [`SetReport`](Event::SetReport) request.
+    ///
+    /// `err` is 0 on success or an errno value otherwise.
+    pub fn set_report_reply(&mut self, id: u32, err: u16) -> io::Result<()> {
+        let mut event = new_event(UHID_SET_REPORT_REPLY);
+        event[4..8].copy_from_slice(&id.to_ne_bytes());
+        event[8..10].copy_from_slice(&err.to_ne_bytes());
+        self.send(&event)
+    }
+
+    /// Wait until an event is available to read, or the timeout expires.
+    ///
+    /// Returns whether an event is available.
+    pub fn poll(&self, timeout: Duration) -> io::Result<bool> {
+        let mut fd = libc::pollfd {
+            fd: self.file.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let timeout = timeout.as_millis().min(i32::MAX as u128) as i32;
+        let ret = unsafe { libc::poll(&mut fd, 1, timeout) };
+        match ret {
+            -1 => {
+                let err = io::Error::last_os_error();
+                if err.kind() == io::ErrorKind::Interrupted {
+                    Ok(false)
+                } else {
+                    Err(err)
+                }
+            }
+            0 => Ok(false),
+            _ => Ok(fd.revents & libc::POLLIN != 0),
+        }
+    }
+
+    /// Read the next event, blocking until one is available.
+    pub fn read_event(&mut self) -> io::Result<Event> {
+        let mut event = vec![0; EVENT_SIZE];
+        let len = self.file.read(&mut event)?;
+        if len < 4 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "uhid event too short",
+            ));
+        }
+        let kind = u32::from_ne_bytes([event[0], event[1], event[2], event[3]]);
+        let u16_at = |i: usize| u16::from_ne_bytes([event[i], event[i + 1]]) as usize;
+        let u32_at = |i: usize| u32::from_ne_bytes([event[i], event[i + 1], event[i + 2], event[i + 3]]);
+        Ok(match kind {
+            UHID_START => Event::Start,
+            UHID_STOP => Event::Stop,
+            UHID_OPEN => Event::Open,
+            UHID_CLOSE => Event::Close,
+            UHID_OUTPUT => {
+                let size = u16_at(4 + DATA_MAX).min(DATA_MAX);
+                Event::Output {
+                    data: event[4..4 + size].to_vec(),
+                }
+            }
+            UHID_GET_REPORT => Event::GetReport {
+                id: u32_at(4),
+                report_number: event[8],
+            },
+            UHID_SET_REPORT => {
+                let size = u16_at(10).min(DATA_MAX);
+                Event::SetReport {
+                    id: u32_at(4),
+                    report_number: event[8],
+                    data: event[12..12 + size].to_vec(),
+                }
+            }
+            other => Event::Other(other),
+        })
+    }
+
+    fn send(&mut self, event: &[u8]) -> io::Result<()> {
+        self.file.write_all(event)
+    }
+}
+
+impl Drop for Uhid {
+    fn drop(&mut self) {
+        // the kernel also destroys the device when the file is closed
+        let _ = self.send(&new_event(UHID_DESTROY));
+    }
+}
+
+fn new_event(kind: u32) -> Vec<u8> {
+    let mut event = vec![0; EVENT_SIZE];
+    event[0..4].copy_from_slice(&kind.to_ne_bytes());
+    event
+}
+
+fn put_data(out: &mut [u8], data: &[u8]) -> io::Result<()> {
+    if data.len() > DATA_MAX {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "uhid data too long",
+        ));
+    }
+    out[..data.len()].copy_from_slice(data);
+    Ok(())
+}
@@ -0,0 +1,416 @@
+//! Force feedback safety limits
+//!
+//! As prolonged use of the controller's motor
+//! may have unknown side effects,
+//! an [`FfLimiter`] can stand between the application and the device,
+//! enforcing a set of [`FfLimits`]:
+//!
+//! - a maximum intensity;
+//! - a maximum continuous vibration time;
+//! - a budget of vibration time over a rolling window (duty cycle),
+//!   followed by a cool-down period once exhausted.
+//!
+//! Requests exceeding the limits are either clamped to fit them
+//! or rejected altogether,
+//! and the caller is always told which of these happened.
+//! Requests to stop force feedback are never rejected.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::time::Duration;
+//! use vcs_classic_hid::force_feedback::{
+//!     limiter::{Adjustment, FfLimiter, FfLimits, FfOutcome},
+//!     FfReport,
+//! };
+//!
+//! let limits = FfLimits {
+//!     max_intensity: 0xC0,
+//!     ..FfLimits::default()
+//! };
+//! let mut limiter = FfLimiter::new(limits);
+//! let outcome = limiter
+//!     .admit(FfReport::new_with_params(0xFF, 20, 20, 2), Duration::from_secs(0))
+//!     .unwrap();
+//! match outcome {
+//!     FfOutcome::Clamped { report, adjustments } => {
+//!         assert_eq!(report.intensity(), 0xC0);
+//!         assert_eq!(adjustments, vec![Adjustment::Intensity]);
+//!     }
+//!     FfOutcome::Accepted(_) => unreachable!(),
+//! }
+//! ```
+use std::{collections::VecDeque, fmt, time::Duration};
+
+use super::{FfReport, TIME_UNIT};
+use crate::Device;
+
+/// The limits enforced by an [`FfLimiter`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FfLimits {
+    /// The maximum intensity of any vibration
+    pub max_intensity: u8,
+    /// The maximum time of uninterrupted vibration
+    pub max_on_time: Duration,
+    /// The maximum fraction of time vibrating within the window,
+    /// from 0 to 1
+    pub duty_cycle: f32,
+    /// The length of the rolling window for the duty cycle
+    pub window: Duration,
+    /// How long to reject vibrations once the budget is exhausted
+    pub cooldown: Duration,
+}
+
+impl Default for FfLimits {
+    fn default() -> Self {
+        FfLimits {
+            max_intensity: 0xFF,
+            max_on_time: Duration::from_secs(2),
+            duty_cycle: 0.5,
+            window: Duration::from_secs(10),
+            cooldown: Duration::from_secs(5),
+        }
+    }
+}
+
+impl FfLimits {
+    /// The total vibration time allowed within the window.
+    pub fn budget(&self) -> Duration {
+        self.window.mul_f32(self.duty_cycle.clamp(0., 1.))
+    }
+}
+
+/// A change made to a request so that it fits the limits.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum Adjustment {
+    /// The intensity was lowered to the maximum intensity
+    Intensity,
+    /// The vibration was shortened to the maximum continuous time
+    OnTime,
+    /// The number of vibrations was reduced to fit the remaining budget
+    Budget,
+}
+
+/// The result of a request admitted by an [`FfLimiter`].
+#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+pub enum FfOutcome {
+    /// The request was sent as is
+    Accepted(FfReport),
+    /// The request was changed to fit the limits before being sent
+    Clamped {
+        /// the report effectively sent
+        report: FfReport,
+        /// the changes made, in order of application
+        adjustments: Vec<Adjustment>,
+    },
+}
+
+impl FfOutcome {
+    /// The report effectively sent.
+    pub fn report(&self) -> FfReport {
+        match self {
+            FfOutcome::Accepted(report) => *report,
+            FfOutcome::Clamped { report, .. } => *report,
+        }
+    }
+}
+
+/// The reason for a request to be rejected by an [`FfLimiter`].
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum Rejection {
+    /// The limiter is cooling down after exhausting the budget
+    CoolingDown {
+        /// the time left until vibrations are accepted again
+        remaining: Duration,
+    },
+    /// There is no vibration time left in the budget,
+    /// a cool-down period has started
+    BudgetExhausted,
+    /// The vibration in progress has already lasted
+    /// the maximum continuous time
+    OnTimeExceeded,
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rejection::CoolingDown { remaining } => write!(
+                f,
+                "force feedback is cooling down ({:.1}s left)",
+                remaining.as_secs_f32()
+            ),
+            Rejection::BudgetExhausted => f.write_str("force feedback budget exhausted"),
+            Rejection::OnTimeExceeded => {
+                f.write_str("force feedback maximum continuous time exceeded")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Rejection {}
+
+/// Error type for sending a report through an [`FfLimiter`].
+#[derive(Debug)]
+pub enum LimiterError<E> {
+    /// The report was rejected by the limiter and not sent
+    Rejected(Rejection),
+    /// The device failed to receive the report
+    Device(E),
+}
+
+impl<E> fmt::Display for LimiterError<E>
+where
+    E: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimiterError::Rejected(rejection) => rejection.fmt(f),
+            LimiterError::Device(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E> std::error::Error for LimiterError<E> where E: fmt::Debug + fmt::Display {}
+
+/// A vibration period sent through an [`FfLimiter`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Period {
+    start: Duration,
+    end: Duration,
+    /// the duration of each vibration
+    up: Duration,
+    /// the time off between each vibration
+    down: Duration,
+    /// the time spent vibrating
+    on: Duration,
+    /// whether the device vibrates without interruption until the end
+    continuous: bool,
+}
+
+impl Period {
+    /// Cut the period short at the given time,
+    /// keeping only the vibrations up to then.
+    fn truncate(&mut self, at: Duration) {
+        let cycle = self.up + self.down;
+        if at >= self.end || cycle.is_zero() {
+            return;
+        }
+        let elapsed = at.saturating_sub(self.start);
+        let cycles = (elapsed.as_nanos() / cycle.as_nanos()) as u32;
+        let rest = elapsed - cycle * cycles;
+        self.on = self.up * cycles + rest.min(self.up);
+        self.end = at;
+    }
+}
+
+/// Enforces safety limits on force feedback reports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FfLimiter {
+    limits: FfLimits,
+    /// the vibration periods sent, oldest first
+    history: VecDeque<Period>,
+    cooldown_until: Option<Duration>,
+}
+
+impl FfLimiter {
+    pub fn new(limits: FfLimits) -> Self {
+        FfLimiter {
+            limits,
+            history: VecDeque::new(),
+            cooldown_until: None,
+        }
+    }
+
+    /// The limits being enforced.
+    pub fn limits(&self) -> &FfLimits {
+        &self.limits
+    }
+
+    /// The vibration time spent within the window ending at `now`.
+    ///
+    /// Vibration periods partially within the window
+    /// are counted in proportion.
+    pub fn used(&mut self, now: Duration) -> Duration {
+        let window_start = now.saturating_sub(self.limits.window);
+        while let Some(period) = self.history.front() {
+            if period.end <= window_start {
+                self.history.pop_front();
+            } else {
+                break;
+            }
+        }
+        self.history
+            .iter()
+            .map(|period| {
+                let span = period.end.saturating_sub(period.start);
+                let overlap = period.end.saturating_sub(period.start.max(window_start));
+                if span.is_zero() || overlap >= span {
+                    period.on
+                } else {
+                    period
+                        .on
+                        .mul_f64(overlap.as_secs_f64() / span.as_secs_f64())
+                }
+            })
+            .sum()
+    }
+
+    /// How long the device has been vibrating without interruption
+    /// up to `now`, through back to back reports.
+    pub fn on_time(&self, now: Duration) -> Duration {
+        let mut since = now;
+        for period in self.history.iter().rev() {
+            if !period.continuous || period.end < since || period.start > since {
+                break;
+            }
+            since = period.start;
+        }
+        now - since
+    }
+
+    /// Whether vibrations are being rejected due to a cool-down,
+    /// and for how long.
+    pub fn cooldown_remaining(&self, now: Duration) -> Option<Duration> {
+        self.cooldown_until
+            .filter(|until| *until > now)
+            .map(|until| until - now)
+    }
+
+    /// Check a report against the limits at the given time,
+    /// recording it as sent if admitted.
+    ///
+    /// `now` is the time elapsed since an arbitrary instant,
+    /// which must be steadily increasing between calls.
+    /// Returns the report to send, possibly clamped.
+    ///
+    /// A report sent while a continuous vibration is in progress
+    /// continues that vibration,
+    /// so it is clamped to the continuous time left,
+    /// or rejected if there is none.
+    /// Any vibration in progress is replaced by the report,
+    /// so the rest of it no longer counts against the budget,
+    /// and a stop ends the continuous vibration.
+    pub fn admit(&mut self, report: FfReport, now: Duration) -> Result<FfOutcome, Rejection> {
+        let is_stop = report.intensity() == 0 || report.up_time() == 0 || report.times() == 0;
+        if is_stop {
+            if let Some(period) = self.history.back_mut() {
+                if period.end >= now {
+                    period.truncate(now);
+                    period.continuous = false;
+                }
+            }
+            return Ok(FfOutcome::Accepted(report));
+        }
+
+        if let Some(remaining) = self.cooldown_remaining(now) {
+            return Err(Rejection::CoolingDown { remaining });
+        }
+        self.cooldown_until = None;
+
+        let mut adjustments = Vec::new();
+        let mut intensity = report.intensity();
+        let mut up_time = u32::from(report.up_time());
+        let down_time = u32::from(report.down_time());
+        let mut times = u32::from(report.times());
+
+        if intensity > self.limits.max_intensity {
+            intensity = self.limits.max_intensity;
+            adjustments.push(Adjustment::Intensity);
+        }
+
+        // a vibration in progress continues into this one
+        let on_time = self.on_time(now);
+        let max_on_time = self.limits.max_on_time.saturating_sub(on_time);
+        let max_on_units = (max_on_time.as_secs_f64() / TIME_UNIT.as_secs_f64()) as u32;
+        let max_on_units = match max_on_units {
+            0 if !on_time.is_zero() => return Err(Rejection::OnTimeExceeded),
+            units => units.max(1),
+        };
+        if down_time == 0 {
+            // back to back vibrations are continuous
+            if up_time * times > max_on_units {
+                if up_time > max_on_units {
+                    up_time = max_on_units;
+                }
+                times = (max_on_units / up_time.max(1)).max(1);
+                adjustments.push(Adjustment::OnTime);
+            }
+        } else if up_time > max_on_units {
+            up_time = max_on_units;
+            adjustments.push(Adjustment::OnTime);
+        }
+
+        // the vibration in progress is replaced by this one,
+        // the rest of it is not spent
+        let interrupted = self.history.back().copied().filter(|p| p.end > now);
+        if let Some(period) = self.history.back_mut() {
+            period.truncate(now);
+        }
+
+        let used = self.used(now);
+        let left = self.limits.budget().saturating_sub(used);
+        let fitting = (left.as_secs_f64() / (TIME_UNIT * up_time).as_secs_f64()) as u32;
+        if fitting == 0 {
+            // not sent, so the vibration in progress goes on
+            if let (Some(interrupted), Some(period)) = (interrupted, self.history.back_mut()) {
+                if period.start == interrupted.start {
+                    *period = interrupted;
+                }
+            }
+            self.cooldown_until = Some(now + self.limits.cooldown);
+            return Err(Rejection::BudgetExhausted);
+        }
+        if fitting < times {
+            times = fitting;
+            adjustments.push(Adjustment::Budget);
+        }
+
+        let sent = FfReport::new_with_params(intensity, up_time as u8, down_time as u8, times as u8);
+        self.history.push_back(Period {
+            start: now,
+            end: now + sent.duration(),
+            up: TIME_UNIT * up_time,
+            down: TIME_UNIT * down_time,
+            on: TIME_UNIT * (up_time * times),
+            continuous: down_time == 0,
+        });
+
+        if adjustments.is_empty() {
+            Ok(FfOutcome::Accepted(sent))
+        } else {
+            Ok(FfOutcome::Clamped {
+                report: sent,
+                adjustments,
+            })
+        }
+    }
+
+    /// Check a report against the limits at the given time,
+    /// and send it to the device if admitted.
+    ///
+    /// `now` is the time elapsed since an arbitrary instant,
+    /// which must be steadily increasing between calls.
+    ///
+    /// **Safety:** although not memory unsafe, the operation must be done
+    /// on a readily available device handle for the Atari Classic Controller.
+    /// The effects on any other device are unknown and potentially dangerous.
+    pub fn send<D>(
+        &mut self,
+        report: FfReport,
+        now: Duration,
+        device: D,
+    ) -> Result<FfOutcome, LimiterError<D::Error>>
+    where
+        D: Device,
+    {
+        let outcome = self.admit(report, now).map_err(LimiterError::Rejected)?;
+        outcome.report().send(device).map_err(LimiterError::Device)?;
+        Ok(outcome)
+    }
+}
+
+impl Default for FfLimiter {
+    fn default() -> Self {
+        FfLimiter::new(FfLimits::default())
+    }
+}
@@ -0,0 +1,114 @@
+//! Force feedback module
+pub mod limiter;
+pub mod pattern;
+use std::time::Duration;
+
+use crate::Device;
+
+/// The approximate duration of one time unit
+/// in the parameters of a force feedback report.
+///
+/// The device does not document its units,
+/// so this value is an approximation.
+pub const TIME_UNIT: Duration = Duration::from_millis(10);
+
+/// A force feedback report.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+#[repr(transparent)]
+pub struct FfReport([u8; 6]);
+
+impl Default for FfReport {
+    fn default() -> Self {
+        FfReport::new()
+    }
+}
+
+impl FfReport {
+    /// Create a new force feedback report
+    /// which disables any ongoing force feedback.
+    pub const fn new() -> Self {
+        FfReport([1, 0, 0, 0, 0, 0])
+    }
+
+    /// Create a new force feedback report
+    /// with the given parameters.
+    ///
+    /// - `intensity`: how intense is the force feedback
+    /// - `up_time`: the duration of each vibration, in [time units](TIME_UNIT)
+    /// - `down_time`: the time off between each vibration, in [time units](TIME_UNIT)
+    /// - `times`: the number of times to vibrate
+    ///
+    /// See [`pattern`] for building effects in real time units.
+    pub const fn new_with_params(intensity: u8, up_time: u8, down_time: u8, times: u8) -> Self {
+        FfReport([
+            1,
+            intensity,
+            up_time,
+            down_time,
+            times,
+            0,
+        ])
+    }
+
+    /// Obtain a force feedback report from the full report packet,
+    /// as sent to the device.
+    ///
+    /// Returns `None` if the data does not represent a force feedback report.
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        match data {
+            [1, intensity, up_time, down_time, times, ..] => Some(FfReport::new_with_params(
+                *intensity, *up_time, *down_time, *times,
+            )),
+            _ => None,
+        }
+    }
+
+    /// The intensity of the force feedback.
+    #[inline]
+    pub fn intensity(&self) -> u8 {
+        self.0[1]
+    }
+
+    /// The duration of each vibration, in [time units](TIME_UNIT).
+    #[inline]
+    pub fn up_time(&self) -> u8 {
+        self.0[2]
+    }
+
+    /// The time off between each vibration, in [time units](TIME_UNIT).
+    #[inline]
+    pub fn down_time(&self) -> u8 {
+        self.0[3]
+    }
+
+    /// The number of times to vibrate.
+    #[inline]
+    pub fn times(&self) -> u8 {
+        self.0[4]
+    }
+
+    /// The approximate total duration of the force feedback.
+    pub fn duration(&self) -> Duration {
+        let units = (u32::from(self.up_time()) + u32::from(self.down_time())) * u32::from(self.times());
+        TIME_UNIT * units
+    }
+
+    /// Send this report as an HID message to the given device.
+    ///  
+    /// **Safety:** although not memory unsafe, the operation must be done
+    /// on a readily available device handle for the Atari Classic Controller.
+    /// The effects on any other device are unknown and potentially dangerous.
+    #[inline]
+    pub fn send<D>(&self, mut device: D) -> Result<(), D::Error>
+    where
+        D: Device,
+    {
+        device.write(&self.0).map(|_| ())
+    }
+}
+
+impl AsRef<[u8]> for FfReport {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
@@ -0,0 +1,370 @@
+//! Force feedback patterns in real time units
+//!
+//! A single [`FfReport`] describes one vibration
+//! repeated a number of times with a fixed intensity,
+//! in raw device units.
+//! An [`FfPattern`] is a sequence of [segments](FfSegment)
+//! expressed in real time and intensities between 0 and 1,
+//! which an [`FfSequencer`] issues to the device one after the other,
+//! so as to emulate envelopes, ramps and other effects.
+//!
+//! A library of ready-made patterns is available in [`effects`].
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # fn main() -> Result<(), hidapi::HidError> {
+//! use std::time::{Duration, Instant};
+//! use vcs_classic_hid::force_feedback::pattern::{effects, FfSequencer, SequencerEvent};
+//!
+//! let mut device = vcs_classic_hid::open()?;
+//! let mut sequencer = FfSequencer::new(effects::heartbeat());
+//! let start = Instant::now();
+//! while sequencer.update(start.elapsed(), &mut device)? == SequencerEvent::Running {
+//!     std::thread::sleep(Duration::from_millis(5));
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use std::time::Duration;
+
+use super::{FfReport, TIME_UNIT};
+use crate::Device;
+
+/// Convert a duration into device time units,
+/// saturating at the maximum supported by a report.
+#[inline]
+fn to_units(duration: Duration) -> u8 {
+    let units = (duration.as_secs_f64() / TIME_UNIT.as_secs_f64()).round();
+    units.clamp(0., 255.) as u8
+}
+
+/// The longest duration of a single vibration supported by a report.
+pub fn max_up_time() -> Duration {
+    TIME_UNIT * 255
+}
+
+/// A step of a force feedback pattern.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FfSegment {
+    /// vibration intensity, from 0 to 1
+    intensity: f32,
+    /// the duration of each vibration
+    on: Duration,
+    /// the time off between each vibration
+    off: Duration,
+    /// the number of vibrations
+    times: u8,
+}
+
+impl FfSegment {
+    /// Create a segment of `times` vibrations
+    /// lasting `on` each and separated by `off`,
+    /// at an intensity between 0 and 1.
+    pub fn new(intensity: f32, on: Duration, off: Duration, times: u8) -> Self {
+        FfSegment {
+            intensity: intensity.clamp(0., 1.),
+            on,
+            off,
+            times,
+        }
+    }
+
+    /// Create a segment with no vibration.
+    pub fn pause(duration: Duration) -> Self {
+        FfSegment {
+            intensity: 0.,
+            on: Duration::from_secs(0),
+            off: duration,
+            times: 1,
+        }
+    }
+
+    /// Whether the segment has no vibration.
+    pub fn is_pause(&self) -> bool {
+        self.intensity == 0. || self.times == 0 || self.on.is_zero()
+    }
+
+    /// The vibration intensity, from 0 to 1.
+    pub fn intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    /// The total duration of the segment.
+    pub fn duration(&self) -> Duration {
+        (self.on + self.off) * u32::from(self.times)
+    }
+
+    /// Convert the segment into a report in device units.
+    ///
+    /// Pauses result in a report which disables force feedback.
+    pub fn to_report(&self) -> FfReport {
+        if self.is_pause() {
+            return FfReport::new();
+        }
+        FfReport::new_with_params(
+            (self.intensity * 255.).round() as u8,
+            to_units(self.on).max(1),
+            to_units(self.off),
+            self.times,
+        )
+    }
+}
+
+/// A sequence of force feedback segments.
+///
+/// ## Example
+///
+/// ```
+/// use std::time::Duration;
+/// use vcs_classic_hid::force_feedback::pattern::FfPattern;
+///
+/// let pattern = FfPattern::new()
+///     .pulse(1., Duration::from_millis(100), Duration::from_millis(50), 2)
+///     .pause(Duration::from_millis(200))
+///     .ramp(0.2, 1., Duration::from_millis(400), 4);
+/// assert_eq!(pattern.segments().len(), 6);
+/// assert_eq!(pattern.duration(), Duration::from_millis(900));
+///
+/// let report = pattern.segments()[0].to_report();
+/// assert_eq!((report.intensity(), report.up_time(), report.down_time(), report.times()), (255, 10, 5, 2));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FfPattern {
+    segments: Vec<FfSegment>,
+}
+
+impl FfPattern {
+    /// Create an empty pattern.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a segment.
+    ///
+    /// Segments with vibrations or gaps longer than what a report supports
+    /// are split into multiple segments, one vibration at a time.
+    pub fn then(mut self, segment: FfSegment) -> Self {
+        if segment.is_pause() || (segment.on <= max_up_time() && segment.off <= max_up_time()) {
+            self.segments.push(segment);
+            return self;
+        }
+        for _ in 0..segment.times {
+            self = self.constant(segment.intensity, segment.on);
+            if !segment.off.is_zero() {
+                self = self.pause(segment.off);
+            }
+        }
+        self
+    }
+
+    /// Append `times` vibrations lasting `on` each and separated by `off`.
+    pub fn pulse(self, intensity: f32, on: Duration, off: Duration, times: u8) -> Self {
+        self.then(FfSegment::new(intensity, on, off, times))
+    }
+
+    /// Append a continuous vibration.
+    ///
+    /// Durations longer than what a report supports
+    /// are split into multiple segments.
+    pub fn constant(mut self, intensity: f32, duration: Duration) -> Self {
+        let mut left = duration;
+        while !left.is_zero() {
+            let on = left.min(max_up_time());
+            self.segments.push(FfSegment::new(intensity, on, Duration::from_secs(0), 1));
+            left -= on;
+        }
+        self
+    }
+
+    /// Append a period with no vibration.
+    pub fn pause(self, duration: Duration) -> Self {
+        self.then(FfSegment::pause(duration))
+    }
+
+    /// Append a continuous vibration
+    /// with an intensity changing linearly from `from` to `to`,
+    /// approximated by the given number of steps.
+    pub fn ramp(mut self, from: f32, to: f32, duration: Duration, steps: u32) -> Self {
+        assert!(steps > 0);
+        let step = duration / steps;
+        for i in 0..steps {
+            let t = if steps > 1 { i as f32 / (steps - 1) as f32 } else { 1. };
+            let intensity = from + (to - from) * t;
+            self = self.constant(intensity, step);
+        }
+        self
+    }
+
+    /// Append another pattern.
+    pub fn chain(mut self, other: &FfPattern) -> Self {
+        self.segments.extend_from_slice(&other.segments);
+        self
+    }
+
+    /// Repeat the whole pattern the given number of times.
+    pub fn repeat(self, times: u32) -> Self {
+        let mut out = FfPattern::new();
+        for _ in 0..times {
+            out = out.chain(&self);
+        }
+        out
+    }
+
+    /// The segments of the pattern, in order.
+    pub fn segments(&self) -> &[FfSegment] {
+        &self.segments
+    }
+
+    /// The total duration of the pattern.
+    pub fn duration(&self) -> Duration {
+        self.segments.iter().map(FfSegment::duration).sum()
+    }
+}
+
+/// Feedback from an [`FfSequencer`] regarding its state after an update.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum SequencerEvent {
+    /// the pattern is still playing
+    Running,
+    /// the whole pattern has been played
+    Ended,
+}
+
+/// Plays a force feedback pattern over time,
+/// sending each segment to the device when it is due.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FfSequencer {
+    pattern: FfPattern,
+    /// the time at which the pattern started
+    start: Option<Duration>,
+    /// the index of the next segment to send
+    next: usize,
+    /// the time at which the next segment is due, relative to the start
+    next_at: Duration,
+}
+
+impl FfSequencer {
+    /// Create a sequencer for the given pattern.
+    /// The pattern starts playing on the first update.
+    pub fn new(pattern: FfPattern) -> Self {
+        FfSequencer {
+            pattern,
+            start: None,
+            next: 0,
+            next_at: Duration::from_secs(0),
+        }
+    }
+
+    /// The pattern being played.
+    pub fn pattern(&self) -> &FfPattern {
+        &self.pattern
+    }
+
+    /// Rewind the pattern to start playing at the given time.
+    pub fn reset(&mut self, now: Duration) {
+        self.start = Some(now);
+        self.next = 0;
+        self.next_at = Duration::from_secs(0);
+    }
+
+    /// Whether all segments have been sent.
+    pub fn is_done(&self) -> bool {
+        self.next >= self.pattern.segments.len()
+    }
+
+    /// Obtain the reports due at the given time,
+    /// marking them as sent.
+    ///
+    /// `now` is the time elapsed since an arbitrary instant,
+    /// which must be steadily increasing between calls.
+    pub fn poll(&mut self, now: Duration) -> Vec<FfReport> {
+        let start = *self.start.get_or_insert(now);
+        let elapsed = now.saturating_sub(start);
+        let mut reports = Vec::new();
+        while let Some(segment) = self.pattern.segments.get(self.next) {
+            if self.next_at > elapsed {
+                break;
+            }
+            if !segment.is_pause() {
+                reports.push(segment.to_report());
+            }
+            self.next_at += segment.duration();
+            self.next += 1;
+        }
+        reports
+    }
+
+    /// Send the reports due at the given time to the device.
+    ///
+    /// `now` is the time elapsed since an arbitrary instant,
+    /// which must be steadily increasing between calls.
+    ///
+    /// **Safety:** although not memory unsafe, the operation must be done
+    /// on a readily available device handle for the Atari Classic Controller.
+    /// The effects on any other device are unknown and potentially dangerous.
+    pub fn update<D>(&mut self, now: Duration, mut device: D) -> Result<SequencerEvent, D::Error>
+    where
+        D: Device,
+    {
+        for report in self.poll(now) {
+            report.send(&mut device)?;
+        }
+        let start = self.start.unwrap_or(now);
+        if self.is_done() && now.saturating_sub(start) >= self.next_at {
+            Ok(SequencerEvent::Ended)
+        } else {
+            Ok(SequencerEvent::Running)
+        }
+    }
+}
+
+/// A library of ready-made force feedback patterns.
+pub mod effects {
+    use super::FfPattern;
+    use std::time::Duration;
+
+    #[inline]
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    /// A double beat followed by a rest, like a heart.
+    pub fn heartbeat() -> FfPattern {
+        FfPattern::new()
+            .pulse(0.8, ms(80), ms(0), 1)
+            .pause(ms(120))
+            .pulse(0.55, ms(100), ms(0), 1)
+            .pause(ms(500))
+    }
+
+    /// A very short, sharp tick.
+    pub fn click() -> FfPattern {
+        FfPattern::new().pulse(0.7, ms(20), ms(0), 1)
+    }
+
+    /// A rapid succession of short vibrations.
+    pub fn buzz() -> FfPattern {
+        FfPattern::new().pulse(0.6, ms(30), ms(20), 8)
+    }
+
+    /// Three strong, long pulses to grab attention.
+    pub fn alarm() -> FfPattern {
+        FfPattern::new()
+            .pulse(1., ms(300), ms(200), 3)
+            .pause(ms(500))
+    }
+
+    /// An engine starting up and idling for the given duration.
+    pub fn engine_rumble(duration: Duration) -> FfPattern {
+        let rev = ms(600).min(duration);
+        FfPattern::new()
+            .ramp(0.25, 0.8, rev, 6)
+            .then(super::FfSegment::new(
+                0.4,
+                ms(40),
+                ms(20),
+                (duration.saturating_sub(rev).as_millis() / 60).min(255) as u8,
+            ))
+    }
+}
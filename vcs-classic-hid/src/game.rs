@@ -0,0 +1,158 @@
+//! Support for writing games on the classic controller.
+//!
+//! A game is typically a state machine
+//! which is updated once per frame with the device and the current tick count,
+//! telling the game loop whether it should keep running.
+//! The passing of time is abstracted by a [`Clock`],
+//! so that the same game can run in real time with a [`FrameClock`]
+//! or as fast as possible with a [`FakeClock`], as in tests.
+//!
+//! ```
+//! # use vcs_classic_hid::{game::{self, FakeClock, GameEvent}, Device};
+//! # struct NoDevice;
+//! # impl Device for NoDevice {
+//! #     type Error = ();
+//! #     fn set_blocking(&mut self, _: bool) -> Result<(), ()> { Ok(()) }
+//! #     fn read(&mut self, _: &mut [u8]) -> Result<usize, ()> { Ok(0) }
+//! #     fn write<T: AsRef<[u8]>>(&mut self, data: T) -> Result<usize, ()> { Ok(data.as_ref().len()) }
+//! # }
+//! # let mut device = NoDevice;
+//! let mut clock = FakeClock::new();
+//! let mut frames = 0;
+//! game::run(&mut device, &mut clock, |_device, ticks| {
+//!     frames += 1;
+//!     Ok(if ticks < 99 { GameEvent::Running } else { GameEvent::Ended })
+//! })?;
+//! assert_eq!(frames, 100);
+//! assert_eq!(clock.ticks(), 100);
+//! # Ok::<(), ()>(())
+//! ```
+use std::time::Duration;
+
+use crate::Device;
+
+/// The event to communicate with the game loop.
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum GameEvent {
+    /// The game should keep running
+    #[default]
+    Running,
+    /// The game has ended, the program should stop
+    Ended,
+}
+
+/// A source of frame ticks for a game loop.
+pub trait Clock {
+    /// Wait for the next frame, if applicable,
+    /// and return its tick count.
+    ///
+    /// Ticks start at 0 for a new clock
+    /// and increase by one on every call.
+    fn tick(&mut self) -> u64;
+}
+
+impl<C> Clock for &mut C
+where
+    C: Clock,
+{
+    fn tick(&mut self) -> u64 {
+        (**self).tick()
+    }
+}
+
+/// A clock in real time,
+/// which sleeps for a fixed amount of time between frames.
+#[derive(Debug, Clone)]
+pub struct FrameClock {
+    frame_time: Duration,
+    ticks: u64,
+}
+
+impl Default for FrameClock {
+    fn default() -> Self {
+        FrameClock::new(Duration::from_millis(25))
+    }
+}
+
+impl FrameClock {
+    pub fn new(frame_time: Duration) -> Self {
+        FrameClock {
+            frame_time,
+            ticks: 0,
+        }
+    }
+
+    /// The time slept between frames.
+    pub fn frame_time(&self) -> Duration {
+        self.frame_time
+    }
+
+    /// The number of ticks produced so far.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+}
+
+impl Clock for FrameClock {
+    fn tick(&mut self) -> u64 {
+        if self.ticks > 0 {
+            std::thread::sleep(self.frame_time);
+        }
+        self.ticks += 1;
+        self.ticks - 1
+    }
+}
+
+/// A clock which never waits,
+/// so that games can be played through as fast as possible.
+#[derive(Debug, Default, Clone)]
+pub struct FakeClock {
+    ticks: u64,
+}
+
+impl FakeClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a clock which starts at the given tick count.
+    pub fn starting_at(ticks: u64) -> Self {
+        FakeClock { ticks }
+    }
+
+    /// The number of ticks produced so far.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Skip the given number of ticks.
+    pub fn skip(&mut self, ticks: u64) {
+        self.ticks += ticks;
+    }
+}
+
+impl Clock for FakeClock {
+    fn tick(&mut self) -> u64 {
+        self.ticks += 1;
+        self.ticks - 1
+    }
+}
+
+/// Run a game loop until the game ends.
+///
+/// `update` is called once per tick of the clock
+/// with the device and the tick count.
+/// The loop stops when it returns [`GameEvent::Ended`] or an error.
+pub fn run<D, C, F>(device: &mut D, mut clock: C, mut update: F) -> Result<(), D::Error>
+where
+    D: Device,
+    C: Clock,
+    F: FnMut(&mut D, u64) -> Result<GameEvent, D::Error>,
+{
+    loop {
+        let ticks = clock.tick();
+        if update(device, ticks)? == GameEvent::Ended {
+            return Ok(());
+        }
+    }
+}
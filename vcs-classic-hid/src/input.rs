@@ -42,6 +42,32 @@ impl StickPosition {
             _ => None,
         }
     }
+
+    /// Obtain the stick position from the directions being pushed,
+    /// such as by arrow keys.
+    /// Opposite directions cancel each other out.
+    ///
+    /// ```
+    /// # use vcs_classic_hid::StickPosition;
+    /// assert_eq!(StickPosition::from_directions(true, true, false, false), StickPosition::UpRight);
+    /// assert_eq!(StickPosition::from_directions(true, true, true, false), StickPosition::Right);
+    /// assert_eq!(StickPosition::from_directions(true, true, true, true), StickPosition::Center);
+    /// ```
+    pub fn from_directions(up: bool, right: bool, down: bool, left: bool) -> Self {
+        let (up, down) = (up && !down, down && !up);
+        let (right, left) = (right && !left, left && !right);
+        match (up, right, down, left) {
+            (true, false, false, false) => StickPosition::Up,
+            (true, true, false, false) => StickPosition::UpRight,
+            (false, true, false, false) => StickPosition::Right,
+            (false, true, true, false) => StickPosition::DownRight,
+            (false, false, true, false) => StickPosition::Down,
+            (false, false, true, true) => StickPosition::DownLeft,
+            (false, false, false, true) => StickPosition::Left,
+            (true, false, false, true) => StickPosition::UpLeft,
+            _ => StickPosition::Center,
+        }
+    }
 }
 
 /// A friendly representation of a game controller input state.
@@ -5,6 +5,19 @@
 //!
 //! For any of these to work, a steady event loop is required.
 //!
+//! Besides the basic effects ([`RotatingLed`], [`Pulsate`], [`Asr`], ...),
+//! a few configurable effects for common use cases are available:
+//! [`Comet`], [`Chase`], [`Spinner`], [`Sparkle`], [`Fire`],
+//! [`ProgressBar`], [`Gauge`], and [`Breathing`] (for the Fuji LED).
+//!
+//! [`Pulsate`] and [`Asr`] can also be rendered with high precision
+//! (see [`HdLedAnimation`]),
+//! which makes slow fades smooth once [dithered](super::dither::Dithered).
+//!
+//! Effects working on an [`LedSelection`] also apply to the Fuji LED
+//! when it is part of the selection (see [`LedSelection::FUJI`]),
+//! whereas effects moving around the ring only apply to the ring.
+//!
 //! ## Example
 //!
 //! ```no_run
@@ -27,7 +40,11 @@
 //! # Ok(())
 //! # }
 //! ```
-use super::{AnimationEvent, LedAnimation, LedReport, LedSelection};
+use super::{
+    dither::{HdLedAnimation, HdLedReport},
+    AnimationEvent, LedAnimation, LedReport, LedSelection,
+};
+use crate::rand::XorShift32;
 
 #[derive(Debug)]
 pub struct RotatingLed;
@@ -41,6 +58,28 @@ impl LedAnimation for RotatingLed {
     }
 }
 
+/// A fixed frame, which does not change over time.
+///
+/// This is useful for taking a snapshot of the LEDs
+/// as the starting point of a [transition](super::transition).
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Still {
+    report: LedReport,
+}
+
+impl Still {
+    pub fn new(report: LedReport) -> Self {
+        Still { report }
+    }
+}
+
+impl LedAnimation for Still {
+    fn update(&mut self, _ticks: u64, report: &mut LedReport) -> AnimationEvent {
+        *report = self.report;
+        AnimationEvent::Running
+    }
+}
+
 /// Incrementally pulsating LEDs
 #[derive(Debug)]
 pub struct OneWayPulsate {
@@ -148,6 +187,21 @@ impl LedAnimation for Pulsate {
         AnimationEvent::Running
     }
 }
+
+impl HdLedAnimation for Pulsate {
+    fn update_hd(&mut self, ticks: u64, report: &mut HdLedReport) -> AnimationEvent {
+        let down = ticks / self.tick_period % 2 == 1;
+        let phase = ticks % self.tick_period;
+        let min = u16::from(self.value_min) * 0x101;
+        let max = u16::from(self.value_max) * 0x101;
+        let step = (phase * u64::from(max - min) / self.tick_period) as u16;
+
+        let value = if down { min + step } else { max - step };
+        report.set_selection(self.selection, value);
+        AnimationEvent::Running
+    }
+}
+
 /// An attack-sustain-release pulse.
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Asr {
@@ -234,3 +288,725 @@ impl LedAnimation for Asr {
         }
     }
 }
+
+/// Combined with a [`Dither`](super::dither::Dither),
+/// the high precision rendering makes
+/// long attack and release phases look continuous.
+///
+/// ## Example
+///
+/// ```
+/// use vcs_classic_hid::led::{
+///     anims::Asr,
+///     dither::{HdLedAnimation, HdLedReport},
+///     LedAnimation, LedSelection,
+/// };
+///
+/// let mut asr = Asr::new_with_params(LedSelection::single(0), 0xFF, 0, 0, 1000);
+/// asr.reset(0);
+/// let mut frame = HdLedReport::new();
+/// asr.update_hd(999, &mut frame);
+/// assert_eq!(frame.get(0), 66);
+/// ```
+impl HdLedAnimation for Asr {
+    fn update_hd(&mut self, ticks: u64, report: &mut HdLedReport) -> AnimationEvent {
+        let dur = ticks - self.base_tick;
+        let max = u64::from(u16::MAX);
+
+        match dur {
+            dur if dur < self.ticks_attack => {
+                let val = (dur * max / self.ticks_attack) as u16;
+                report.set_selection(self.selection, val);
+                AnimationEvent::Running
+            }
+            dur if dur < self.ticks_attack + self.ticks_sustain => {
+                report.set_selection(self.selection, u16::MAX);
+                AnimationEvent::Running
+            }
+            dur if dur < self.ticks_attack + self.ticks_sustain + self.ticks_release => {
+                let dur = dur - self.ticks_attack - self.ticks_sustain;
+                let val = !((dur * max / self.ticks_release) as u16);
+                report.set_selection(self.selection, val);
+                AnimationEvent::Running
+            }
+            _ => {
+                report.set_selection(self.selection, 0);
+                AnimationEvent::Ended
+            }
+        }
+    }
+}
+
+/// Direction of movement around the LED ring.
+///
+/// LED indices grow clockwise, starting from the bottom of the ring.
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum Direction {
+    #[default]
+    Clockwise,
+    CounterClockwise,
+}
+
+impl Direction {
+    /// Obtain the LED which is `steps` positions away from `origin`
+    /// when moving in this direction.
+    pub fn offset(self, origin: u8, steps: i64) -> u8 {
+        let steps = match self {
+            Direction::Clockwise => steps,
+            Direction::CounterClockwise => -steps,
+        };
+        (i64::from(origin) + steps).rem_euclid(24) as u8
+    }
+}
+
+/// A single bright LED travelling around the ring,
+/// followed by a fading tail.
+///
+/// ## Example
+///
+/// ```
+/// use vcs_classic_hid::led::{anims::{Comet, Direction}, LedAnimation, LedReport};
+///
+/// let mut comet = Comet::new_with_params(Direction::Clockwise, 4, 3, 0xFF);
+/// let mut report = LedReport::new();
+/// comet.update(8, &mut report);
+/// // head at LED 2, tail at LEDs 1, 0 and 23
+/// assert_eq!(&report.as_ref()[3..6], &[0x7F, 0xBF, 0xFF]);
+/// assert_eq!(report.as_ref()[3 + 23], 0x3F);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Comet {
+    direction: Direction,
+    tick_period: u64,
+    tail_length: u8,
+    value: u8,
+}
+
+impl Comet {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create a comet moving in the given direction,
+    /// advancing one LED every `tick_period` ticks.
+    /// The tail may be at most 23 LEDs long.
+    pub fn new_with_params(direction: Direction, tick_period: u64, tail_length: u8, value: u8) -> Self {
+        assert!(tick_period > 0);
+        Comet {
+            direction,
+            tick_period,
+            tail_length: tail_length.min(23),
+            value,
+        }
+    }
+}
+
+impl Default for Comet {
+    fn default() -> Self {
+        Comet {
+            direction: Direction::Clockwise,
+            tick_period: 4,
+            tail_length: 6,
+            value: 0xFF,
+        }
+    }
+}
+
+impl LedAnimation for Comet {
+    fn update(&mut self, ticks: u64, report: &mut LedReport) -> AnimationEvent {
+        let head = self.direction.offset(0, (ticks / self.tick_period) as i64);
+        let tail_length = u16::from(self.tail_length);
+        for i in (1..=tail_length).rev() {
+            let value = u16::from(self.value) * (tail_length + 1 - i) / (tail_length + 1);
+            report.set(self.direction.offset(head, -i64::from(i)), value as u8);
+        }
+        report.set(head, self.value);
+        AnimationEvent::Running
+    }
+}
+
+/// Multiple equally spaced LEDs chasing each other around the ring.
+///
+/// ## Example
+///
+/// ```
+/// use vcs_classic_hid::led::{anims::{Chase, Direction}, LedAnimation, LedReport};
+///
+/// let mut chase = Chase::new_with_params(3, Direction::CounterClockwise, 10, 0xC0);
+/// let mut report = LedReport::new();
+/// chase.update(25, &mut report);
+/// let lit: Vec<usize> = (0..24).filter(|i| report.as_ref()[3 + i] == 0xC0).collect();
+/// assert_eq!(lit, vec![6, 14, 22]);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Chase {
+    dots: u8,
+    direction: Direction,
+    tick_period: u64,
+    value: u8,
+}
+
+impl Chase {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create a chase of `dots` LEDs (from 1 to 24),
+    /// advancing one LED every `tick_period` ticks.
+    pub fn new_with_params(dots: u8, direction: Direction, tick_period: u64, value: u8) -> Self {
+        assert!((1..=24).contains(&dots));
+        assert!(tick_period > 0);
+        Chase {
+            dots,
+            direction,
+            tick_period,
+            value,
+        }
+    }
+}
+
+impl Default for Chase {
+    fn default() -> Self {
+        Chase {
+            dots: 4,
+            direction: Direction::Clockwise,
+            tick_period: 6,
+            value: 0xFF,
+        }
+    }
+}
+
+impl LedAnimation for Chase {
+    fn update(&mut self, ticks: u64, report: &mut LedReport) -> AnimationEvent {
+        let head = self.direction.offset(0, (ticks / self.tick_period) as i64);
+        for k in 0..u16::from(self.dots) {
+            let spacing = k * 24 / u16::from(self.dots);
+            report.set(self.direction.offset(head, i64::from(spacing)), self.value);
+        }
+        AnimationEvent::Running
+    }
+}
+
+/// A rotating arc which stretches and shrinks as it spins,
+/// akin to a loading indicator.
+///
+/// ## Example
+///
+/// ```
+/// use vcs_classic_hid::led::{anims::{Direction, Spinner}, LedAnimation, LedReport};
+///
+/// let mut spinner = Spinner::new_with_params(Direction::Clockwise, 2, 2, 5, 0xFF);
+/// let mut report = LedReport::new();
+/// // 3 steps in: the head is at LED 3 and the arc is 5 LEDs long
+/// spinner.update(6, &mut report);
+/// assert_eq!(&report.as_ref()[3..8], &[0xFF, 0xFF, 0xFF, 0xFF, 0]);
+/// assert_eq!(&report.as_ref()[3 + 22..3 + 24], &[0, 0xFF]);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Spinner {
+    direction: Direction,
+    tick_period: u64,
+    min_length: u8,
+    max_length: u8,
+    value: u8,
+}
+
+impl Spinner {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create a spinner advancing one LED every `tick_period` ticks,
+    /// with an arc length varying between `min_length` and `max_length`
+    /// (from 1 to 24).
+    pub fn new_with_params(
+        direction: Direction,
+        tick_period: u64,
+        min_length: u8,
+        max_length: u8,
+        value: u8,
+    ) -> Self {
+        assert!(tick_period > 0);
+        assert!(min_length > 0 && min_length <= max_length && max_length <= 24);
+        Spinner {
+            direction,
+            tick_period,
+            min_length,
+            max_length,
+            value,
+        }
+    }
+}
+
+impl Default for Spinner {
+    fn default() -> Self {
+        Spinner {
+            direction: Direction::Clockwise,
+            tick_period: 3,
+            min_length: 2,
+            max_length: 10,
+            value: 0xFF,
+        }
+    }
+}
+
+impl LedAnimation for Spinner {
+    fn update(&mut self, ticks: u64, report: &mut LedReport) -> AnimationEvent {
+        let step = ticks / self.tick_period;
+        let head = self.direction.offset(0, step as i64);
+
+        // triangle wave between the minimum and maximum length
+        let amplitude = u64::from(self.max_length - self.min_length);
+        let length = if amplitude == 0 {
+            self.min_length
+        } else {
+            let phase = step % (2 * amplitude);
+            let grow = if phase < amplitude { phase } else { 2 * amplitude - phase };
+            self.min_length + grow as u8
+        };
+
+        for i in 0..i64::from(length) {
+            report.set(self.direction.offset(head, -i), self.value);
+        }
+        AnimationEvent::Running
+    }
+}
+
+/// Random twinkles over a selection of LEDs.
+///
+/// Each LED may light up at any tick with the given chance,
+/// then fades out over time.
+/// Sequences are reproducible for the same seed.
+///
+/// ## Example
+///
+/// ```
+/// use vcs_classic_hid::led::{anims::Sparkle, LedAnimation, LedReport, LedSelection};
+///
+/// let mut a = Sparkle::new_with_params(LedSelection::ALL, 16, 0xFF, 0x20, 42);
+/// let mut b = Sparkle::new_with_params(LedSelection::ALL, 16, 0xFF, 0x20, 42);
+/// a.reset(0);
+/// b.reset(0);
+/// for ticks in 1..100 {
+///     let (mut report_a, mut report_b) = (LedReport::new(), LedReport::new());
+///     a.update(ticks, &mut report_a);
+///     b.update(ticks, &mut report_b);
+///     assert_eq!(report_a, report_b);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sparkle {
+    selection: LedSelection,
+    chance: u8,
+    value: u8,
+    decay: u8,
+    seed: u32,
+    rng: XorShift32,
+    levels: [u8; 25],
+    last_tick: Option<u64>,
+}
+
+impl Sparkle {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn new_with_selection(selection: LedSelection) -> Self {
+        Sparkle {
+            selection,
+            ..Sparkle::default()
+        }
+    }
+
+    /// Create a sparkle effect.
+    ///
+    /// - `chance`: the odds of an LED lighting up on each tick, out of 256
+    /// - `value`: the intensity of a twinkle when it lights up
+    /// - `decay`: how much intensity a twinkle loses per tick
+    /// - `seed`: the seed of the pseudo-random number generator
+    pub fn new_with_params(selection: LedSelection, chance: u8, value: u8, decay: u8, seed: u32) -> Self {
+        Sparkle {
+            selection,
+            chance,
+            value,
+            decay,
+            seed,
+            rng: XorShift32::new(seed),
+            levels: [0; 25],
+            last_tick: None,
+        }
+    }
+}
+
+impl Default for Sparkle {
+    fn default() -> Self {
+        Sparkle::new_with_params(LedSelection::ALL, 8, 0xFF, 0x18, 1)
+    }
+}
+
+impl LedAnimation for Sparkle {
+    fn reset(&mut self, ticks: u64) {
+        self.rng = XorShift32::new(self.seed);
+        self.levels = [0; 25];
+        self.last_tick = Some(ticks);
+    }
+
+    fn update(&mut self, ticks: u64, report: &mut LedReport) -> AnimationEvent {
+        // simulate every tick since the last update,
+        // up to the point where all twinkles would have faded anyway
+        let steps = match self.last_tick {
+            Some(last) => ticks.saturating_sub(last).min(256),
+            None => 1,
+        };
+        self.last_tick = Some(ticks);
+
+        for _ in 0..steps {
+            for (i, level) in self.levels.iter_mut().enumerate() {
+                if !self.selection.contains(i as u8) {
+                    continue;
+                }
+                *level = level.saturating_sub(self.decay);
+                if self.rng.next_u8() < self.chance {
+                    *level = self.value;
+                }
+            }
+        }
+
+        for (i, level) in self.levels.iter().enumerate() {
+            if self.selection.contains(i as u8) {
+                report.set(i as u8, *level);
+            }
+        }
+        AnimationEvent::Running
+    }
+}
+
+/// Flickering fire over a selection of LEDs.
+///
+/// The intensity of each LED wanders randomly
+/// between the given minimum and maximum values.
+/// Sequences are reproducible for the same seed.
+///
+/// ## Example
+///
+/// ```
+/// use vcs_classic_hid::led::{anims::Fire, LedAnimation, LedReport, LedSelection};
+///
+/// let mut fire = Fire::new_with_params(LedSelection::ALL, 0x40, 0xC0, 7);
+/// fire.reset(0);
+/// for ticks in 1..100 {
+///     let mut report = LedReport::new();
+///     fire.update(ticks, &mut report);
+///     assert!(report.as_ref()[3..27].iter().all(|v| (0x40..=0xC0).contains(v)));
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fire {
+    selection: LedSelection,
+    value_min: u8,
+    value_max: u8,
+    seed: u32,
+    rng: XorShift32,
+    heat: [u8; 25],
+    last_tick: Option<u64>,
+}
+
+impl Fire {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn new_with_selection(selection: LedSelection) -> Self {
+        Fire {
+            selection,
+            ..Fire::default()
+        }
+    }
+
+    pub fn new_with_params(selection: LedSelection, value_min: u8, value_max: u8, seed: u32) -> Self {
+        assert!(value_min <= value_max);
+        Fire {
+            selection,
+            value_min,
+            value_max,
+            seed,
+            rng: XorShift32::new(seed),
+            heat: [value_min; 25],
+            last_tick: None,
+        }
+    }
+}
+
+impl Default for Fire {
+    fn default() -> Self {
+        Fire::new_with_params(LedSelection::ALL, 0x30, 0xFF, 1)
+    }
+}
+
+impl LedAnimation for Fire {
+    fn reset(&mut self, ticks: u64) {
+        self.rng = XorShift32::new(self.seed);
+        self.heat = [self.value_min; 25];
+        self.last_tick = Some(ticks);
+    }
+
+    fn update(&mut self, ticks: u64, report: &mut LedReport) -> AnimationEvent {
+        let steps = match self.last_tick {
+            Some(last) => ticks.saturating_sub(last).min(64),
+            None => 1,
+        };
+        self.last_tick = Some(ticks);
+
+        let range = u16::from(self.value_max - self.value_min) + 1;
+        for _ in 0..steps {
+            for heat in self.heat.iter_mut() {
+                // move towards a random target, for a smooth flicker
+                let target = u16::from(self.value_min) + u16::from(self.rng.next_u8()) * range / 256;
+                *heat = ((u16::from(*heat) * 3 + target) / 4) as u8;
+            }
+        }
+
+        for (i, heat) in self.heat.iter().enumerate() {
+            if self.selection.contains(i as u8) {
+                report.set(i as u8, *heat);
+            }
+        }
+        AnimationEvent::Running
+    }
+}
+
+/// A bar filling up the ring from a starting LED,
+/// according to a progress value between 0 and 1.
+///
+/// The LED at the edge of the bar is partially lit
+/// to show fractional progress.
+///
+/// ## Example
+///
+/// ```
+/// use vcs_classic_hid::led::{anims::{Direction, ProgressBar}, LedAnimation, LedReport};
+///
+/// let mut bar = ProgressBar::new_with_params(12, Direction::Clockwise, 0xFF);
+/// bar.set_progress(0.125);
+/// let mut report = LedReport::new();
+/// bar.update(0, &mut report);
+/// assert_eq!(&report.as_ref()[3 + 11..3 + 16], &[0, 0xFF, 0xFF, 0xFF, 0]);
+///
+/// bar.set_progress(0.0625);
+/// let mut report = LedReport::new();
+/// bar.update(0, &mut report);
+/// assert_eq!(&report.as_ref()[3 + 11..3 + 16], &[0, 0xFF, 0x7F, 0, 0]);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ProgressBar {
+    start: u8,
+    direction: Direction,
+    value: u8,
+    progress: f32,
+}
+
+impl ProgressBar {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn new_with_params(start: u8, direction: Direction, value: u8) -> Self {
+        assert!(start < 24);
+        ProgressBar {
+            start,
+            direction,
+            value,
+            progress: 0.,
+        }
+    }
+
+    /// The current progress, between 0 and 1.
+    pub fn progress(&self) -> f32 {
+        self.progress
+    }
+
+    /// Change the progress shown, clamped between 0 and 1.
+    pub fn set_progress(&mut self, progress: f32) {
+        self.progress = progress.clamp(0., 1.);
+    }
+}
+
+impl Default for ProgressBar {
+    /// A progress bar starting from the top of the ring.
+    fn default() -> Self {
+        ProgressBar::new_with_params(12, Direction::Clockwise, 0xFF)
+    }
+}
+
+impl LedAnimation for ProgressBar {
+    fn update(&mut self, _ticks: u64, report: &mut LedReport) -> AnimationEvent {
+        let filled = self.progress * 24.;
+        let full = filled as i64;
+        for i in 0..full {
+            report.set(self.direction.offset(self.start, i), self.value);
+        }
+        if full < 24 {
+            let partial = (filled.fract() * f32::from(self.value)) as u8;
+            if partial > 0 {
+                report.set(self.direction.offset(self.start, full), partial);
+            }
+        }
+        AnimationEvent::Running
+    }
+}
+
+/// A needle pointing to a level between 0 and 1
+/// over an arc of the ring.
+///
+/// When the level falls between two LEDs,
+/// the intensity of the needle is split between them.
+///
+/// ## Example
+///
+/// ```
+/// use vcs_classic_hid::led::{anims::{Direction, Gauge}, LedAnimation, LedReport};
+///
+/// // an arc of 9 LEDs starting from LED 0, with a dim background
+/// let mut gauge = Gauge::new_with_params(0, 9, Direction::Clockwise, 0xFF, 0x10);
+/// gauge.set_level(0.5);
+/// let mut report = LedReport::new();
+/// gauge.update(0, &mut report);
+/// assert_eq!(&report.as_ref()[3..3 + 10], &[0x10, 0x10, 0x10, 0x10, 0xFF, 0x10, 0x10, 0x10, 0x10, 0]);
+///
+/// gauge.set_level(0.5625);
+/// let mut report = LedReport::new();
+/// gauge.update(0, &mut report);
+/// assert_eq!(&report.as_ref()[3 + 4..3 + 6], &[0x7F, 0x7F]);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Gauge {
+    start: u8,
+    sweep: u8,
+    direction: Direction,
+    value: u8,
+    background: u8,
+    level: f32,
+}
+
+impl Gauge {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create a gauge over an arc of `sweep` LEDs (from 2 to 24)
+    /// beginning at the `start` LED.
+    /// The LEDs of the arc are set to the `background` value,
+    /// unless it is zero.
+    pub fn new_with_params(start: u8, sweep: u8, direction: Direction, value: u8, background: u8) -> Self {
+        assert!(start < 24);
+        assert!((2..=24).contains(&sweep));
+        Gauge {
+            start,
+            sweep,
+            direction,
+            value,
+            background,
+            level: 0.,
+        }
+    }
+
+    /// The current level, between 0 and 1.
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    /// Change the level shown, clamped between 0 and 1.
+    pub fn set_level(&mut self, level: f32) {
+        self.level = level.clamp(0., 1.);
+    }
+}
+
+impl Default for Gauge {
+    /// A gauge over the ring from the bottom left to the bottom right,
+    /// much like a speedometer.
+    fn default() -> Self {
+        Gauge::new_with_params(3, 19, Direction::Clockwise, 0xFF, 0)
+    }
+}
+
+impl LedAnimation for Gauge {
+    fn update(&mut self, _ticks: u64, report: &mut LedReport) -> AnimationEvent {
+        if self.background > 0 {
+            for i in 0..i64::from(self.sweep) {
+                report.set(self.direction.offset(self.start, i), self.background);
+            }
+        }
+
+        let position = self.level * f32::from(self.sweep - 1);
+        let index = position as i64;
+        let fraction = position.fract();
+        let value = f32::from(self.value);
+        report.set(
+            self.direction.offset(self.start, index),
+            (value * (1. - fraction)) as u8,
+        );
+        if fraction > 0. {
+            report.set(
+                self.direction.offset(self.start, index + 1),
+                (value * fraction) as u8,
+            );
+        }
+        AnimationEvent::Running
+    }
+}
+
+/// A smooth, breathing-like pulse on the Fuji LED.
+///
+/// ## Example
+///
+/// ```
+/// use vcs_classic_hid::led::{anims::Breathing, LedAnimation, LedReport};
+///
+/// let mut breathing = Breathing::new_with_params(100, 0, 0xFF);
+/// let mut report = LedReport::new();
+/// breathing.update(0, &mut report);
+/// assert_eq!(report.as_ref()[2], 0);
+/// breathing.update(50, &mut report);
+/// assert_eq!(report.as_ref()[2], 0xFF);
+/// breathing.update(125, &mut report);
+/// assert_eq!(report.as_ref()[2], 0x80);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Breathing {
+    tick_period: u64,
+    value_min: u8,
+    value_max: u8,
+}
+
+impl Breathing {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create a breathing effect with a full cycle of `tick_period` ticks.
+    pub fn new_with_params(tick_period: u64, value_min: u8, value_max: u8) -> Self {
+        assert!(tick_period > 0);
+        assert!(value_min <= value_max);
+        Breathing {
+            tick_period,
+            value_min,
+            value_max,
+        }
+    }
+}
+
+impl Default for Breathing {
+    fn default() -> Self {
+        Breathing::new_with_params(160, 0x08, 0xFF)
+    }
+}
+
+impl LedAnimation for Breathing {
+    fn update(&mut self, ticks: u64, report: &mut LedReport) -> AnimationEvent {
+        let phase = (ticks % self.tick_period) as f32 / self.tick_period as f32;
+        let amount = (1. - (phase * std::f32::consts::TAU).cos()) / 2.;
+        let range = f32::from(self.value_max - self.value_min);
+        report.set_fuji(self.value_min + (amount * range).round() as u8);
+        AnimationEvent::Running
+    }
+}
@@ -0,0 +1,232 @@
+//! Perceptual correction of LED intensities
+//!
+//! The intensities in an [`LedReport`] are sent to the device
+//! as raw PWM duty cycles,
+//! which the human eye does not perceive linearly:
+//! a fade from 0 to 255 seems to linger at full brightness,
+//! and mid values are hard to distinguish.
+//!
+//! An [`LedCorrection`] describes a transformation stage
+//! (gamma curve, global brightness cap and per-LED calibration)
+//! to be applied right before a report is sent,
+//! so that animations can work on perceived intensities.
+//! Wrap a device with [`LedCorrection::wrap`]
+//! to apply it to all LED reports written.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # fn main() -> Result<(), hidapi::HidError> {
+//! use vcs_classic_hid::{Device, LedReport};
+//! use vcs_classic_hid::led::correction::LedCorrection;
+//!
+//! let mut device = LedCorrection::perceptual()
+//!     .with_brightness(0x80)
+//!     .wrap(vcs_classic_hid::open()?);
+//! // shown at a perceived half intensity, capped at half power
+//! device.write(LedReport::filled(0x80))?;
+//! # Ok(())
+//! # }
+//! ```
+use super::LedReport;
+use crate::Device;
+
+/// The gamma value of the [perceptual](LedCorrection::perceptual) correction.
+pub const PERCEPTUAL_GAMMA: f32 = 2.2;
+
+/// A transformation of LED intensities
+/// to be applied before sending reports to the device.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LedCorrection {
+    gamma: f32,
+    brightness: u8,
+    /// scale factors for the Fuji LED followed by the ring LEDs
+    calibration: Option<[u8; 25]>,
+    /// cached combination of gamma and brightness
+    table: [u8; 256],
+}
+
+impl Default for LedCorrection {
+    /// By default, no correction is applied.
+    fn default() -> Self {
+        LedCorrection::new()
+    }
+}
+
+impl LedCorrection {
+    /// Create an identity correction, which keeps intensities unchanged.
+    pub fn new() -> Self {
+        LedCorrection::with_params(1., 0xFF, None)
+    }
+
+    /// Create a correction following the typical gamma curve of human vision,
+    /// so that intensities in reports are linear to perceived brightness.
+    pub fn perceptual() -> Self {
+        LedCorrection::new().with_gamma(PERCEPTUAL_GAMMA)
+    }
+
+    fn with_params(gamma: f32, brightness: u8, calibration: Option<[u8; 25]>) -> Self {
+        let mut correction = LedCorrection {
+            gamma,
+            brightness,
+            calibration,
+            table: [0; 256],
+        };
+        correction.build_table();
+        correction
+    }
+
+    /// Use the given gamma curve.
+    /// A value of 1 is linear, higher values darken mid intensities.
+    pub fn with_gamma(self, gamma: f32) -> Self {
+        assert!(gamma > 0., "gamma must be positive");
+        LedCorrection::with_params(gamma, self.brightness, self.calibration)
+    }
+
+    /// Limit the maximum intensity of all LEDs to the given value.
+    pub fn with_brightness(self, brightness: u8) -> Self {
+        LedCorrection::with_params(self.gamma, brightness, self.calibration)
+    }
+
+    /// Scale the intensity of each LED individually,
+    /// where 255 keeps the LED at full range.
+    ///
+    /// This can compensate for LEDs which appear brighter than others.
+    pub fn with_calibration(self, ring: [u8; 24], fuji: u8) -> Self {
+        let mut calibration = [0; 25];
+        calibration[0] = fuji;
+        calibration[1..].copy_from_slice(&ring);
+        LedCorrection::with_params(self.gamma, self.brightness, Some(calibration))
+    }
+
+    /// The gamma value used.
+    pub fn gamma(&self) -> f32 {
+        self.gamma
+    }
+
+    /// The global brightness cap.
+    pub fn brightness(&self) -> u8 {
+        self.brightness
+    }
+
+    fn build_table(&mut self) {
+        let brightness = f32::from(self.brightness);
+        for (i, out) in self.table.iter_mut().enumerate() {
+            let level = (i as f32 / 255.).powf(self.gamma);
+            *out = (level * brightness).round() as u8;
+        }
+    }
+
+    /// Correct a single intensity value, ignoring per-LED calibration.
+    #[inline]
+    pub fn correct(&self, value: u8) -> u8 {
+        self.table[value as usize]
+    }
+
+    /// Obtain a corrected copy of the given report.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use vcs_classic_hid::led::{correction::LedCorrection, LedReport};
+    ///
+    /// let correction = LedCorrection::perceptual().with_brightness(0xC0);
+    /// let mut report = LedReport::new();
+    /// report.set(0, 0xFF);
+    /// report.set(1, 0x80);
+    /// let report = correction.apply(&report);
+    /// assert_eq!(&report.as_ref()[3..6], &[0xC0, 0x2A, 0]);
+    /// ```
+    pub fn apply(&self, report: &LedReport) -> LedReport {
+        let mut out = *report;
+        self.apply_in_place(&mut out.0[2..27]);
+        out
+    }
+
+    /// Correct the intensities of a raw LED report payload,
+    /// starting at the Fuji LED.
+    fn apply_in_place(&self, leds: &mut [u8]) {
+        for (i, led) in leds.iter_mut().enumerate() {
+            let mut value = self.correct(*led);
+            if let Some(scale) = self.calibration.as_ref().and_then(|c| c.get(i)) {
+                value = (u16::from(value) * u16::from(*scale) / 255) as u8;
+            }
+            *led = value;
+        }
+    }
+
+    /// Wrap a device so that this correction is applied
+    /// to all LED reports written to it.
+    pub fn wrap<D>(self, device: D) -> CorrectedDevice<D>
+    where
+        D: Device,
+    {
+        CorrectedDevice {
+            device,
+            correction: self,
+        }
+    }
+}
+
+/// A device which applies an [`LedCorrection`]
+/// to the LED reports written to it.
+///
+/// All other reports are passed through untouched.
+#[derive(Debug)]
+pub struct CorrectedDevice<D> {
+    device: D,
+    correction: LedCorrection,
+}
+
+impl<D> CorrectedDevice<D> {
+    /// The correction being applied.
+    pub fn correction(&self) -> &LedCorrection {
+        &self.correction
+    }
+
+    /// Replace the correction being applied.
+    pub fn set_correction(&mut self, correction: LedCorrection) {
+        self.correction = correction;
+    }
+
+    /// Obtain the inner device.
+    pub fn into_inner(self) -> D {
+        self.device
+    }
+}
+
+impl<D> Device for CorrectedDevice<D>
+where
+    D: Device,
+{
+    type Error = D::Error;
+
+    fn set_blocking(&mut self, blocking: bool) -> Result<(), Self::Error> {
+        self.device.set_blocking(blocking)
+    }
+
+    fn read(&mut self, out: &mut [u8]) -> Result<usize, Self::Error> {
+        self.device.read(out)
+    }
+
+    fn write<T>(&mut self, data: T) -> Result<usize, Self::Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        let data = data.as_ref();
+        match data {
+            // LED report with LED data
+            [2, len, ..] if *len > 0 && data.len() > 2 => {
+                let mut corrected = data.to_vec();
+                let end = (2 + usize::from(*len)).min(corrected.len());
+                self.correction.apply_in_place(&mut corrected[2..end]);
+                self.device.write(corrected)
+            }
+            _ => self.device.write(data),
+        }
+    }
+
+    fn reset_leds(&mut self) -> Result<(), Self::Error> {
+        self.device.reset_leds()
+    }
+}
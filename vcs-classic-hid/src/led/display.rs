@@ -0,0 +1,422 @@
+//! Showing numbers and text with the LEDs
+//!
+//! The controller has no screen,
+//! but the LED ring can still convey some information:
+//!
+//! - [`NumberDisplay`] shows an integer on the ring,
+//!   in one of a few [styles](NumberStyle);
+//! - [`Morse`] blinks a message in Morse code on the Fuji LED;
+//! - [`ClockFace`] shows the time with hour and minute hands.
+//!
+//! All of them are [`LedAnimation`]s.
+//! Unless stated otherwise, they are laid out clockwise
+//! from the top of the ring.
+use super::{mapping, AnimationEvent, LedAnimation, LedReport};
+
+/// The LED at the top of the ring, where displays start.
+const TOP: u8 = 12;
+
+/// The way a number is shown on the ring.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum NumberStyle {
+    /// Digits in the given base (from 2 to 12),
+    /// most significant digit first.
+    ///
+    /// The ring is split into segments of `base` LEDs:
+    /// a dim separator followed by one LED per unit of the digit.
+    /// As such, the ring fits `24 / base` digits.
+    /// Numbers too large to fit are shown with all digits at their maximum.
+    Digits { base: u8 },
+    /// One LED per unit, with every fifth mark brighter.
+    /// Numbers above 24 are shown as 24.
+    Tally,
+    /// The bits of the number,
+    /// least significant bit first,
+    /// with unset bits shown dimly.
+    /// Numbers too large to fit in 24 bits are shown with all bits set.
+    Binary,
+}
+
+impl Default for NumberStyle {
+    fn default() -> Self {
+        NumberStyle::Digits { base: 10 }
+    }
+}
+
+/// An integer shown on the ring.
+///
+/// The whole ring is drawn,
+/// LEDs not used by the number are turned off.
+///
+/// ## Example
+///
+/// ```
+/// use vcs_classic_hid::led::{display::{NumberDisplay, NumberStyle}, LedAnimation, LedReport};
+///
+/// let mut display = NumberDisplay::new_with_params(23, NumberStyle::Digits { base: 6 }, 0xFF, 0x20);
+/// let mut report = LedReport::new();
+/// display.update(0, &mut report);
+/// // 23 is 0035 in base 6
+/// assert_eq!(
+///     report.ring(),
+///     [
+///         0x20, 0xFF, 0xFF, 0xFF, 0, 0, // third digit (3)
+///         0x20, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, // fourth digit (5)
+///         0x20, 0, 0, 0, 0, 0, // first digit (0), from the top
+///         0x20, 0, 0, 0, 0, 0, // second digit (0)
+///     ]
+/// );
+/// ```
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub struct NumberDisplay {
+    value: u32,
+    style: NumberStyle,
+    on: u8,
+    off: u8,
+}
+
+impl NumberDisplay {
+    /// Show a number in decimal digits.
+    pub fn new(value: u32) -> Self {
+        NumberDisplay {
+            value,
+            ..NumberDisplay::default()
+        }
+    }
+
+    /// Show a number in the given style,
+    /// where `on` is the intensity of lit marks
+    /// and `off` the intensity of separators and unset bits.
+    pub fn new_with_params(value: u32, style: NumberStyle, on: u8, off: u8) -> Self {
+        if let NumberStyle::Digits { base } = style {
+            assert!((2..=12).contains(&base), "unsupported base {}", base);
+        }
+        NumberDisplay {
+            value,
+            style,
+            on,
+            off,
+        }
+    }
+
+    /// The number shown.
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+
+    /// Change the number shown.
+    pub fn set_value(&mut self, value: u32) {
+        self.value = value;
+    }
+
+    #[inline]
+    fn led(i: u8) -> u8 {
+        (TOP + i) % 24
+    }
+}
+
+impl Default for NumberDisplay {
+    fn default() -> Self {
+        NumberDisplay::new_with_params(0, NumberStyle::default(), 0xFF, 0x20)
+    }
+}
+
+impl LedAnimation for NumberDisplay {
+    fn update(&mut self, _ticks: u64, report: &mut LedReport) -> AnimationEvent {
+        report.clear();
+        match self.style {
+            NumberStyle::Digits { base } => {
+                let num_digits = 24 / u32::from(base);
+                let base32 = u32::from(base);
+                let overflow = base32
+                    .checked_pow(num_digits)
+                    .map(|max| self.value >= max)
+                    .unwrap_or(false);
+                for d in 0..num_digits {
+                    let digit = if overflow {
+                        base32 - 1
+                    } else {
+                        self.value / base32.pow(num_digits - 1 - d) % base32
+                    };
+                    let start = (d * base32) as u8;
+                    report.set(Self::led(start), self.off);
+                    for k in 1..base {
+                        let value = if u32::from(k) <= digit { self.on } else { 0 };
+                        report.set(Self::led(start + k), value);
+                    }
+                }
+            }
+            NumberStyle::Tally => {
+                let marks = self.value.min(24) as u8;
+                for i in 0..marks {
+                    let value = if (i + 1) % 5 == 0 {
+                        self.on
+                    } else {
+                        // regular marks at two thirds of the intensity
+                        (u16::from(self.on) * 2 / 3) as u8
+                    };
+                    report.set(Self::led(i), value);
+                }
+            }
+            NumberStyle::Binary => {
+                let value = self.value.min((1 << 24) - 1);
+                for i in 0..24 {
+                    let bit = (value >> i) & 1 == 1;
+                    report.set(Self::led(i), if bit { self.on } else { self.off });
+                }
+            }
+        }
+        AnimationEvent::Running
+    }
+}
+
+/// The Morse code of a character, as a string of dots (`.`)
+/// and dashes (`-`),
+/// or `None` if the character has no Morse code.
+fn morse_code(c: char) -> Option<&'static str> {
+    let code = match c.to_ascii_uppercase() {
+        'A' => ".-",
+        'B' => "-...",
+        'C' => "-.-.",
+        'D' => "-..",
+        'E' => ".",
+        'F' => "..-.",
+        'G' => "--.",
+        'H' => "....",
+        'I' => "..",
+        'J' => ".---",
+        'K' => "-.-",
+        'L' => ".-..",
+        'M' => "--",
+        'N' => "-.",
+        'O' => "---",
+        'P' => ".--.",
+        'Q' => "--.-",
+        'R' => ".-.",
+        'S' => "...",
+        'T' => "-",
+        'U' => "..-",
+        'V' => "...-",
+        'W' => ".--",
+        'X' => "-..-",
+        'Y' => "-.--",
+        'Z' => "--..",
+        '0' => "-----",
+        '1' => ".----",
+        '2' => "..---",
+        '3' => "...--",
+        '4' => "....-",
+        '5' => ".....",
+        '6' => "-....",
+        '7' => "--...",
+        '8' => "---..",
+        '9' => "----.",
+        '.' => ".-.-.-",
+        ',' => "--..--",
+        '?' => "..--..",
+        '!' => "-.-.--",
+        '/' => "-..-.",
+        '-' => "-....-",
+        '=' => "-...-",
+        '+' => ".-.-.",
+        '@' => ".--.-.",
+        _ => return None,
+    };
+    Some(code)
+}
+
+/// A message blinked in Morse code on the Fuji LED.
+///
+/// A dot lasts one time unit, a dash three.
+/// Symbols are separated by one unit,
+/// letters by three units and words by seven units.
+/// Unsupported characters are skipped.
+///
+/// ## Example
+///
+/// ```
+/// use vcs_classic_hid::led::{display::Morse, AnimationEvent, LedAnimation, LedReport};
+///
+/// let mut morse = Morse::new_with_params("ET", 2, 0xFF, false);
+/// morse.reset(0);
+/// let frames: Vec<_> = (0..12)
+///     .map(|ticks| {
+///         let mut report = LedReport::new();
+///         morse.update(ticks, &mut report);
+///         report.fuji()
+///     })
+///     .collect();
+/// // E (.), letter gap, T (-)
+/// assert_eq!(frames, [0xFF, 0xFF, 0, 0, 0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF]);
+///
+/// let mut report = LedReport::new();
+/// assert_eq!(morse.update(14, &mut report), AnimationEvent::Ended);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Morse {
+    /// on/off state of each time unit
+    units: Vec<bool>,
+    tick_unit: u64,
+    value: u8,
+    repeat: bool,
+    base_tick: u64,
+}
+
+impl Morse {
+    /// Blink the message with a time unit of 6 ticks, only once.
+    pub fn new(message: &str) -> Self {
+        Morse::new_with_params(message, 6, 0xFF, false)
+    }
+
+    /// Blink the message with the given time unit in ticks,
+    /// starting over after a word gap if `repeat` is true.
+    pub fn new_with_params(message: &str, tick_unit: u64, value: u8, repeat: bool) -> Self {
+        assert!(tick_unit > 0);
+        Morse {
+            units: Self::encode(message),
+            tick_unit,
+            value,
+            repeat,
+            base_tick: 0,
+        }
+    }
+
+    fn encode(message: &str) -> Vec<bool> {
+        let mut units = Vec::new();
+        for (w, word) in message.split_whitespace().enumerate() {
+            let mut first_letter = true;
+            for code in word.chars().filter_map(morse_code) {
+                if !first_letter {
+                    // letter gap
+                    units.extend_from_slice(&[false; 3]);
+                } else if w > 0 && !units.is_empty() {
+                    // word gap
+                    units.extend_from_slice(&[false; 7]);
+                }
+                first_letter = false;
+                for (s, symbol) in code.chars().enumerate() {
+                    if s > 0 {
+                        units.push(false);
+                    }
+                    let len = if symbol == '-' { 3 } else { 1 };
+                    units.extend(std::iter::repeat_n(true, len));
+                }
+            }
+        }
+        units
+    }
+
+    /// The duration of the message in ticks, excluding repetitions.
+    pub fn duration(&self) -> u64 {
+        self.units.len() as u64 * self.tick_unit
+    }
+}
+
+impl LedAnimation for Morse {
+    fn reset(&mut self, ticks: u64) {
+        self.base_tick = ticks;
+    }
+
+    fn update(&mut self, ticks: u64, report: &mut LedReport) -> AnimationEvent {
+        let mut unit = (ticks.saturating_sub(self.base_tick) / self.tick_unit) as usize;
+        if self.repeat {
+            // word gap before repeating
+            unit %= self.units.len() + 7;
+        }
+        match self.units.get(unit) {
+            Some(on) => {
+                report.set_fuji(if *on { self.value } else { 0 });
+                AnimationEvent::Running
+            }
+            None if self.repeat => {
+                report.set_fuji(0);
+                AnimationEvent::Running
+            }
+            None => {
+                report.set_fuji(0);
+                AnimationEvent::Ended
+            }
+        }
+    }
+}
+
+/// A clock face with hour and minute hands,
+/// with 12 o'clock at the top of the ring.
+///
+/// Hands between two LEDs are split between them.
+///
+/// ## Example
+///
+/// ```
+/// use vcs_classic_hid::led::{display::ClockFace, LedAnimation, LedReport};
+///
+/// let mut clock = ClockFace::new();
+/// clock.set_time(3, 30);
+/// let mut report = LedReport::new();
+/// clock.update(0, &mut report);
+/// // the minute hand points down, the hour hand is halfway between 3 and 4
+/// assert_eq!(report.get(0), 0xFF);
+/// assert_eq!(report.get(19), 0xC0);
+///
+/// clock.set_time(3, 15);
+/// let mut report = LedReport::new();
+/// clock.update(0, &mut report);
+/// // both hands point right, the hour hand partially on the next LED
+/// assert_eq!(report.get(18), 0xFF);
+/// assert_eq!(report.get(19), 0x60);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ClockFace {
+    hours: u8,
+    minutes: u8,
+    hour_value: u8,
+    minute_value: u8,
+    marks: u8,
+}
+
+impl ClockFace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a clock face with the given intensities
+    /// for the hour hand, the minute hand,
+    /// and the marks at each hour (not shown if 0).
+    pub fn new_with_params(hour_value: u8, minute_value: u8, marks: u8) -> Self {
+        ClockFace {
+            hours: 0,
+            minutes: 0,
+            hour_value,
+            minute_value,
+            marks,
+        }
+    }
+
+    /// Change the time shown, in hours (0 to 23) and minutes (0 to 59).
+    pub fn set_time(&mut self, hours: u8, minutes: u8) {
+        self.hours = hours % 24;
+        self.minutes = minutes % 60;
+    }
+}
+
+impl Default for ClockFace {
+    fn default() -> Self {
+        ClockFace::new_with_params(0xC0, 0xFF, 0)
+    }
+}
+
+impl LedAnimation for ClockFace {
+    fn update(&mut self, _ticks: u64, report: &mut LedReport) -> AnimationEvent {
+        if self.marks > 0 {
+            for i in (0..24).step_by(2) {
+                report.set(i, self.marks);
+            }
+        }
+        let ring = mapping::RingMapping::new();
+        let minutes = f32::from(self.minutes);
+        let hours = f32::from(self.hours % 12) + minutes / 60.;
+        mapping::draw_point(report, ring.degrees_to_position(hours * 30.), self.hour_value);
+        mapping::draw_point(report, ring.degrees_to_position(minutes * 6.), self.minute_value);
+        AnimationEvent::Running
+    }
+}
@@ -0,0 +1,277 @@
+//! High precision LED frames with temporal dithering
+//!
+//! The controller only accepts 8-bit LED intensities,
+//! so slow fades near the low end of the range visibly step
+//! from one value to the next.
+//! An [`HdLedReport`] holds 16-bit intensities instead,
+//! and a [`Dither`] turns a sequence of these frames into [`LedReport`]s,
+//! carrying the quantization error of each LED over to the next frame.
+//! Over a few frames, the average intensity of each LED
+//! matches the intended high precision value.
+//!
+//! Animations which can render high precision frames
+//! implement [`HdLedAnimation`],
+//! and are turned into regular [`LedAnimation`]s
+//! with their output dithered by wrapping them in [`Dithered`].
+//!
+//! ## Example
+//!
+//! ```
+//! use vcs_classic_hid::led::{dither::{Dither, HdLedReport}, LedReport};
+//!
+//! let mut dither = Dither::new();
+//! // an intensity of 2.5 (out of 255) on the first LED
+//! let mut frame = HdLedReport::new();
+//! frame.set(0, 0x0280);
+//!
+//! let sum: u32 = (0..4)
+//!     .map(|_| u32::from(dither.render(&frame).as_ref()[3]))
+//!     .sum();
+//! assert_eq!(sum, 10);
+//! ```
+use super::{AnimationEvent, LedAnimation, LedReport, LedSelection, FUJI};
+use crate::Device;
+
+/// An LED report with 16 bits of precision per LED.
+///
+/// Intensities range from 0 (off) to `0xFFFF` (maximum intensity),
+/// where the most significant byte matches the intensity
+/// of a regular [`LedReport`].
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct HdLedReport {
+    fuji: u16,
+    ring: [u16; 24],
+}
+
+impl HdLedReport {
+    /// Create a new report with all LEDs off.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new report with the ring filled with the given intensity.
+    #[inline]
+    pub fn filled(value: u16) -> Self {
+        let mut x = Self::default();
+        x.fill(value);
+        x
+    }
+
+    /// Turn all ring of LEDs off.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.fill(0)
+    }
+
+    /// Set all LEDs in the ring to the given value.
+    #[inline]
+    pub fn fill(&mut self, value: u16) {
+        self.ring.fill(value);
+    }
+
+    /// Set the Fuji LED to a value.
+    #[inline]
+    pub fn set_fuji(&mut self, value: u16) {
+        self.fuji = value;
+    }
+
+    /// Set a LED in the ring (or the Fuji LED via [`FUJI`]) to a value.
+    #[inline]
+    pub fn set(&mut self, led: u8, value: u16) {
+        match led {
+            FUJI => self.fuji = value,
+            led => self.ring[led as usize] = value,
+        }
+    }
+
+    /// Set a LED in the ring (or the Fuji LED via [`FUJI`])
+    /// to a fractional intensity between 0 and 1.
+    #[inline]
+    pub fn set_level(&mut self, led: u8, level: f32) {
+        self.set(led, level_to_u16(level));
+    }
+
+    /// Set a selection of LEDs to a value.
+    #[inline]
+    pub fn set_selection(&mut self, selection: LedSelection, value: u16) {
+        for i in selection.iter() {
+            self.set(i, value);
+        }
+    }
+
+    /// Get the value of the Fuji LED.
+    #[inline]
+    pub fn fuji(&self) -> u16 {
+        self.fuji
+    }
+
+    /// Get the value of a LED in the ring (or the Fuji LED via [`FUJI`]).
+    #[inline]
+    pub fn get(&self, led: u8) -> u16 {
+        match led {
+            FUJI => self.fuji,
+            led => self.ring[led as usize],
+        }
+    }
+
+    /// Convert to a regular LED report by truncation, without dithering.
+    pub fn to_report(&self) -> LedReport {
+        let mut report = LedReport::new();
+        report.set_fuji((self.fuji >> 8) as u8);
+        for (i, value) in self.ring.iter().enumerate() {
+            report.set(i as u8, (value >> 8) as u8);
+        }
+        report
+    }
+}
+
+impl From<LedReport> for HdLedReport {
+    fn from(report: LedReport) -> Self {
+        let mut out = HdLedReport::new();
+        // 0xFF maps exactly to 0xFFFF
+        out.fuji = u16::from(report.fuji()) * 0x101;
+        for (led, value) in out.ring.iter_mut().zip(&report.ring()) {
+            *led = u16::from(*value) * 0x101;
+        }
+        out
+    }
+}
+
+#[inline]
+fn level_to_u16(level: f32) -> u16 {
+    (level.clamp(0., 1.) * f32::from(u16::MAX)).round() as u16
+}
+
+/// A temporal dithering stage,
+/// converting high precision frames into LED reports.
+///
+/// The same `Dither` should be used for all frames of a sequence,
+/// as it keeps the quantization error of each LED
+/// to be compensated in subsequent frames.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Dither {
+    /// accumulated error for the Fuji LED followed by the ring LEDs,
+    /// in units of 1/257 of an 8-bit step
+    error: [i32; 25],
+}
+
+impl Dither {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget all accumulated error.
+    pub fn reset(&mut self) {
+        self.error = [0; 25];
+    }
+
+    #[inline]
+    fn quantize(&mut self, index: usize, value: u16) -> u8 {
+        let target = i32::from(value) + self.error[index];
+        let out = ((target + 128) / 257).clamp(0, 255);
+        self.error[index] = target - out * 257;
+        out as u8
+    }
+
+    /// Produce the next LED report for the given high precision frame.
+    pub fn render(&mut self, frame: &HdLedReport) -> LedReport {
+        let mut report = LedReport::new();
+        let fuji = self.quantize(0, frame.fuji);
+        report.set_fuji(fuji);
+        for (i, value) in frame.ring.iter().enumerate() {
+            let value = self.quantize(i + 1, *value);
+            report.set(i as u8, value);
+        }
+        report
+    }
+
+    /// Produce the next LED report for the given high precision frame
+    /// and send it to the device.
+    ///
+    /// **Safety:** although not memory unsafe, the operation must be done
+    /// on a readily available device handle for the Atari Classic Controller.
+    /// The effects on any other device are unknown and potentially dangerous.
+    pub fn send<D>(&mut self, frame: &HdLedReport, device: D) -> Result<(), D::Error>
+    where
+        D: Device,
+    {
+        self.render(frame).send(device)
+    }
+}
+
+/// An LED animation which can be rendered
+/// with 16 bits of precision per LED.
+pub trait HdLedAnimation: LedAnimation {
+    /// Update the state of the animation
+    /// with 16 bits of precision per LED.
+    ///
+    /// This is the high precision counterpart of
+    /// [`LedAnimation::update`].
+    fn update_hd(&mut self, ticks: u64, report: &mut HdLedReport) -> AnimationEvent;
+}
+
+/// An animation rendered with high precision and temporal dithering.
+///
+/// LEDs which the animation leaves untouched
+/// keep the value of the report being updated.
+///
+/// ## Example
+///
+/// ```
+/// use vcs_classic_hid::led::{anims::Asr, dither::Dithered, LedAnimation, LedReport, LedSelection};
+///
+/// // a release so slow that it stays between levels 1 and 2 for 100 ticks
+/// let mut fade = Dithered::new(Asr::new_with_params(LedSelection::ALL, 0xFF, 0, 0, 25_500));
+/// fade.reset(0);
+/// let values: Vec<u8> = (25_300..25_400)
+///     .map(|ticks| {
+///         let mut report = LedReport::new();
+///         fade.update(ticks, &mut report);
+///         report.get(0)
+///     })
+///     .collect();
+/// assert!(values.contains(&1) && values.contains(&2));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Dithered<A> {
+    animation: A,
+    dither: Dither,
+}
+
+impl<A> Dithered<A> {
+    /// Wrap a high precision animation.
+    pub fn new(animation: A) -> Self {
+        Dithered {
+            animation,
+            dither: Dither::new(),
+        }
+    }
+
+    /// The animation being dithered.
+    pub fn inner(&self) -> &A {
+        &self.animation
+    }
+
+    /// Retrieve the animation being dithered.
+    pub fn into_inner(self) -> A {
+        self.animation
+    }
+}
+
+impl<A> LedAnimation for Dithered<A>
+where
+    A: HdLedAnimation,
+{
+    fn reset(&mut self, ticks: u64) {
+        self.animation.reset(ticks);
+        self.dither.reset();
+    }
+
+    fn update(&mut self, ticks: u64, report: &mut LedReport) -> AnimationEvent {
+        let mut frame = HdLedReport::from(*report);
+        let event = self.animation.update_hd(ticks, &mut frame);
+        *report = self.dither.render(&frame);
+        event
+    }
+}
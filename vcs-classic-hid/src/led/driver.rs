@@ -0,0 +1,271 @@
+//! Background LED rendering
+//!
+//! Writing an LED report on every iteration of a program's main loop
+//! floods the USB connection with reports,
+//! most of which are often identical to the previous one.
+//! An [`LedDriver`] renders an animation on a dedicated thread
+//! at a fixed frame rate instead,
+//! only sending reports when they change
+//! and never faster than a configured rate.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # fn main() -> Result<(), hidapi::HidError> {
+//! use std::time::Duration;
+//! use vcs_classic_hid::led::{anims::Comet, driver::{LedDriver, LedDriverConfig}};
+//!
+//! let device = vcs_classic_hid::open()?;
+//! let driver = LedDriver::spawn(device, Comet::new(), LedDriverConfig::default());
+//! std::thread::sleep(Duration::from_secs(5));
+//! println!("{:?}", driver.stats());
+//! let mut device = driver.stop()?;
+//! # Ok(())
+//! # }
+//! ```
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use super::{AnimationEvent, LedAnimation, LedReport};
+use crate::Device;
+
+/// Configuration of an [`LedDriver`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LedDriverConfig {
+    /// The number of frames rendered per second,
+    /// each frame advancing the animation by one tick
+    pub fps: u32,
+    /// The maximum number of reports sent to the device per second
+    pub max_send_rate: u32,
+    /// Whether to skip sending a report identical to the last one sent
+    pub skip_unchanged: bool,
+}
+
+impl Default for LedDriverConfig {
+    fn default() -> Self {
+        LedDriverConfig {
+            fps: 40,
+            max_send_rate: 40,
+            skip_unchanged: true,
+        }
+    }
+}
+
+/// Frame timing statistics of an [`LedDriver`].
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+pub struct FrameStats {
+    /// The number of frames rendered
+    pub frames: u64,
+    /// The number of reports sent to the device
+    pub sent: u64,
+    /// The number of frames not sent for being identical to the last one
+    pub unchanged: u64,
+    /// The number of frames not sent due to the maximum send rate
+    pub throttled: u64,
+    /// The number of frames which finished after their deadline
+    pub late: u64,
+    /// The number of frames never rendered
+    /// because the driver fell too far behind schedule
+    pub dropped: u64,
+    /// The longest time spent rendering and sending a frame
+    pub worst_frame_time: Duration,
+}
+
+#[derive(Debug)]
+struct Shared<A> {
+    animation: Mutex<A>,
+    stats: Mutex<FrameStats>,
+    stop: AtomicBool,
+}
+
+/// An LED animation renderer running on its own thread.
+///
+/// The driver takes ownership of the device until it is stopped.
+/// When the animation ends, the driver sends its last frame
+/// and stops rendering.
+#[derive(Debug)]
+pub struct LedDriver<D, A>
+where
+    D: Device,
+{
+    shared: Arc<Shared<A>>,
+    handle: Option<JoinHandle<Result<D, D::Error>>>,
+}
+
+impl<D, A> LedDriver<D, A>
+where
+    D: Device + Send + 'static,
+    D::Error: Send + 'static,
+    A: LedAnimation + Send + 'static,
+{
+    /// Start rendering the animation to the device on a new thread.
+    pub fn spawn(device: D, animation: A, config: LedDriverConfig) -> Self {
+        assert!(config.fps > 0, "frame rate must be positive");
+        assert!(config.max_send_rate > 0, "send rate must be positive");
+
+        let shared = Arc::new(Shared {
+            animation: Mutex::new(animation),
+            stats: Mutex::new(FrameStats::default()),
+            stop: AtomicBool::new(false),
+        });
+
+        let handle = {
+            let shared = Arc::clone(&shared);
+            std::thread::spawn(move || run(device, &shared, config))
+        };
+
+        LedDriver {
+            shared,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl<D, A> LedDriver<D, A>
+where
+    D: Device,
+{
+    /// Obtain exclusive access to the animation being rendered,
+    /// so that it can be inspected or modified.
+    ///
+    /// Rendering is blocked while the guard is held.
+    pub fn animation(&self) -> MutexGuard<'_, A> {
+        self.shared
+            .animation
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Obtain a snapshot of the frame timing statistics.
+    pub fn stats(&self) -> FrameStats {
+        *self.shared.stats.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Whether the driver is no longer rendering,
+    /// either because the animation ended
+    /// or due to an error writing to the device.
+    pub fn is_finished(&self) -> bool {
+        self.handle.as_ref().map(|h| h.is_finished()).unwrap_or(true)
+    }
+
+    /// Stop rendering and retrieve the device.
+    ///
+    /// Returns the first error which occurred while writing to the device,
+    /// if any.
+    pub fn stop(mut self) -> Result<D, D::Error> {
+        self.shared.stop.store(true, Ordering::Release);
+        let handle = self.handle.take().expect("driver thread handle should exist");
+        match handle.join() {
+            Ok(result) => result,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+}
+
+impl<D, A> Drop for LedDriver<D, A>
+where
+    D: Device,
+{
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            self.shared.stop.store(true, Ordering::Release);
+            let _ = handle.join();
+        }
+    }
+}
+
+fn run<D, A>(mut device: D, shared: &Shared<A>, config: LedDriverConfig) -> Result<D, D::Error>
+where
+    D: Device,
+    A: LedAnimation,
+{
+    let period = Duration::from_secs(1) / config.fps;
+    let min_send_interval = Duration::from_secs(1) / config.max_send_rate;
+
+    let mut ticks: u64 = 0;
+    let mut last_sent: Option<(LedReport, Instant)> = None;
+    let mut deadline = Instant::now() + period;
+
+    shared
+        .animation
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .reset(ticks);
+
+    while !shared.stop.load(Ordering::Acquire) {
+        let frame_start = Instant::now();
+
+        let mut report = LedReport::new();
+        let event = shared
+            .animation
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .update(ticks, &mut report);
+
+        let mut sent = false;
+        let mut unchanged = false;
+        let mut throttled = false;
+        match last_sent {
+            Some((last, _)) if config.skip_unchanged && last == report => unchanged = true,
+            Some((_, at)) if frame_start.duration_since(at) < min_send_interval => throttled = true,
+            _ => {
+                report.send(&mut device)?;
+                last_sent = Some((report, frame_start));
+                sent = true;
+            }
+        }
+
+        let now = Instant::now();
+        let mut dropped = 0;
+        let late = now > deadline;
+        if late {
+            // skip the frames which can no longer be rendered on time,
+            // so that the animation keeps up with real time
+            let behind = now.duration_since(deadline);
+            dropped = (behind.as_nanos() / period.as_nanos()) as u64;
+            deadline += period * (dropped as u32);
+        }
+
+        {
+            let mut stats = shared.stats.lock().unwrap_or_else(|e| e.into_inner());
+            stats.frames += 1;
+            stats.sent += u64::from(sent);
+            stats.unchanged += u64::from(unchanged);
+            stats.throttled += u64::from(throttled);
+            stats.late += u64::from(late);
+            stats.dropped += dropped;
+            stats.worst_frame_time = stats.worst_frame_time.max(now.duration_since(frame_start));
+        }
+
+        if event == AnimationEvent::Ended {
+            if throttled {
+                // the last frame must not be left out,
+                // send it as soon as the send rate allows
+                if let Some((_, at)) = last_sent {
+                    if let Some(wait) =
+                        (at + min_send_interval).checked_duration_since(Instant::now())
+                    {
+                        std::thread::sleep(wait);
+                    }
+                }
+                report.send(&mut device)?;
+                shared.stats.lock().unwrap_or_else(|e| e.into_inner()).sent += 1;
+            }
+            break;
+        }
+
+        ticks += 1 + dropped;
+        if let Some(wait) = deadline.checked_duration_since(Instant::now()) {
+            std::thread::sleep(wait);
+        }
+        deadline += period;
+    }
+
+    Ok(device)
+}
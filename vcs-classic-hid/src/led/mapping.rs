@@ -0,0 +1,185 @@
+//! Mapping between paddle roll, angles and positions in the LED ring
+//!
+//! Positions in the ring are fractional LED indices,
+//! from 0 (inclusive) to 24 (exclusive),
+//! where the center of LED `i` is at position `i`.
+//! LED indices grow clockwise, starting from the bottom of the ring.
+//!
+//! The paddle roll (from 0 to 1023) covers the ring once,
+//! starting from LED 0.
+//! Angles, on the other hand, are relative to a [`RingMapping`],
+//! which defines where the zero angle is and in which direction angles grow.
+//! Throughout the crate, including [`LedSelection::arc`](super::LedSelection::arc),
+//! angles follow the default mapping unless told otherwise:
+//! 0 degrees is the top of the ring (LED 12), growing clockwise.
+//!
+//! ## Example
+//!
+//! ```
+//! use vcs_classic_hid::led::{mapping::{self, RingMapping}, LedReport};
+//!
+//! // the paddle at a quarter turn
+//! assert_eq!(mapping::roll_to_led(256), 6);
+//!
+//! // 0 degrees is the top of the ring, 90 degrees is the right side
+//! let ring = RingMapping::new();
+//! assert_eq!(ring.degrees_to_led(0.), 12);
+//! assert_eq!(ring.degrees_to_led(90.), 18);
+//!
+//! // a point halfway between LEDs 3 and 4
+//! let mut report = LedReport::new();
+//! mapping::draw_point(&mut report, 3.5, 0xFF);
+//! assert_eq!(report.get(3), 0x80);
+//! assert_eq!(report.get(4), 0x80);
+//! ```
+use super::{anims::Direction, LedReport};
+
+/// The number of distinct paddle roll values, covering the whole ring.
+pub const ROLL_RANGE: u16 = 1024;
+
+/// Wrap a position to the range of the ring, from 0 to 24.
+#[inline]
+pub fn wrap_position(position: f32) -> f32 {
+    let p = position.rem_euclid(24.);
+    // rem_euclid may round up to the modulus itself
+    if p >= 24. {
+        0.
+    } else {
+        p
+    }
+}
+
+/// The LED closest to the given position.
+#[inline]
+pub fn position_to_led(position: f32) -> u8 {
+    (wrap_position(position).round() as u8) % 24
+}
+
+/// Convert a paddle roll (from 0 to 1023) into a position in the ring.
+///
+/// Each LED covers an equal share of the roll range,
+/// with LED 0 covering the first values.
+#[inline]
+pub fn roll_to_position(roll: u16) -> f32 {
+    let roll = roll % ROLL_RANGE;
+    wrap_position(f32::from(roll) * 24. / f32::from(ROLL_RANGE) - 0.5)
+}
+
+/// Convert a position in the ring into a paddle roll (from 0 to 1023).
+#[inline]
+pub fn position_to_roll(position: f32) -> u16 {
+    let roll = ((wrap_position(position) + 0.5) * f32::from(ROLL_RANGE) / 24.).floor() as u16;
+    roll % ROLL_RANGE
+}
+
+/// The LED which corresponds to the given paddle roll (from 0 to 1023).
+#[inline]
+pub fn roll_to_led(roll: u16) -> u8 {
+    ((u32::from(roll % ROLL_RANGE) * 24 / u32::from(ROLL_RANGE)) as u8).min(23)
+}
+
+/// Light up a point at a fractional position in the ring,
+/// splitting its intensity between the two nearest LEDs.
+///
+/// LEDs already brighter than their share of the point are kept as is.
+pub fn draw_point(report: &mut LedReport, position: f32, value: u8) {
+    let position = wrap_position(position);
+    let index = position.floor();
+    let fraction = position - index;
+    let index = index as u8 % 24;
+    let next = (index + 1) % 24;
+    let value = f32::from(value);
+
+    let a = (value * (1. - fraction)).round() as u8;
+    let b = (value * fraction).round() as u8;
+    report.set(index, report.get(index).max(a));
+    if b > 0 {
+        report.set(next, report.get(next).max(b));
+    }
+}
+
+/// The orientation of angles over the LED ring.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RingMapping {
+    /// the position of the zero angle
+    offset: f32,
+    direction: Direction,
+}
+
+impl Default for RingMapping {
+    /// The zero angle is at the top of the ring,
+    /// with angles growing clockwise.
+    fn default() -> Self {
+        RingMapping {
+            offset: 12.,
+            direction: Direction::Clockwise,
+        }
+    }
+}
+
+impl RingMapping {
+    /// Create a mapping with the zero angle at the top of the ring,
+    /// with angles growing clockwise.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a mapping with the zero angle at the given position,
+    /// with angles growing in the given direction.
+    pub fn new_with_params(offset: f32, direction: Direction) -> Self {
+        RingMapping {
+            offset: wrap_position(offset),
+            direction,
+        }
+    }
+
+    /// Convert an angle in degrees into a position in the ring.
+    pub fn degrees_to_position(&self, degrees: f32) -> f32 {
+        let steps = degrees / 15.;
+        match self.direction {
+            Direction::Clockwise => wrap_position(self.offset + steps),
+            Direction::CounterClockwise => wrap_position(self.offset - steps),
+        }
+    }
+
+    /// Convert a position in the ring into an angle in degrees,
+    /// from 0 to 360.
+    pub fn position_to_degrees(&self, position: f32) -> f32 {
+        let steps = match self.direction {
+            Direction::Clockwise => position - self.offset,
+            Direction::CounterClockwise => self.offset - position,
+        };
+        wrap_position(steps) * 15.
+    }
+
+    /// Convert an angle in radians into a position in the ring.
+    pub fn radians_to_position(&self, radians: f32) -> f32 {
+        self.degrees_to_position(radians.to_degrees())
+    }
+
+    /// Convert a position in the ring into an angle in radians,
+    /// from 0 to 2π.
+    pub fn position_to_radians(&self, position: f32) -> f32 {
+        self.position_to_degrees(position).to_radians()
+    }
+
+    /// The LED closest to the given angle in degrees.
+    pub fn degrees_to_led(&self, degrees: f32) -> u8 {
+        position_to_led(self.degrees_to_position(degrees))
+    }
+
+    /// The LED closest to the given angle in radians.
+    pub fn radians_to_led(&self, radians: f32) -> u8 {
+        position_to_led(self.radians_to_position(radians))
+    }
+
+    /// Convert a paddle roll (from 0 to 1023) into an angle in degrees.
+    pub fn roll_to_degrees(&self, roll: u16) -> f32 {
+        self.position_to_degrees(roll_to_position(roll))
+    }
+
+    /// Convert an angle in degrees into a paddle roll (from 0 to 1023).
+    pub fn degrees_to_roll(&self, degrees: f32) -> u16 {
+        position_to_roll(self.degrees_to_position(degrees))
+    }
+}
@@ -1,5 +1,12 @@
 //! LED manipulation module
 pub mod anims;
+pub mod correction;
+pub mod dither;
+pub mod display;
+pub mod driver;
+pub mod mapping;
+pub mod scheduler;
+pub mod transition;
 use crate::Device;
 
 /// A behavioral construct for effects and animations on the controller's LEDs.
@@ -52,21 +59,82 @@ impl Quadrant {
     }
 }
 
-/// An arbitrary selection of leds in the ring.
+/// The index identifying the Fuji LED,
+/// which can be used alongside the indices of the LEDs in the ring
+/// (0 to 23) in selections and reports.
+pub const FUJI: u8 = 24;
+
+/// An arbitrary selection of leds in the ring and the Fuji LED.
+///
+/// Selections are represented as a compact bit mask,
+/// where bit `i` corresponds to the LED of index `i`,
+/// so they can be cheaply combined and computed on every frame.
+/// The Fuji LED is identified by the index [`FUJI`].
+/// Operations based on the geometry of the ring
+/// (such as [`rotate`](LedSelection::rotate) and [`not`](LedSelection::not))
+/// leave the Fuji LED as is.
+///
+/// ## Example
+///
+/// ```
+/// use vcs_classic_hid::led::LedSelection;
+///
+/// let top = LedSelection::span(12, 2);
+/// assert_eq!(top.iter().collect::<Vec<_>>(), vec![10, 11, 12, 13, 14]);
+///
+/// let bottom = top.rotate(12);
+/// assert_eq!(bottom.iter().collect::<Vec<_>>(), vec![0, 1, 2, 22, 23]);
+///
+/// let rest = top.or(bottom).not();
+/// assert_eq!(rest.count(), 14);
+/// assert!(!rest.contains(12));
+///
+/// // 0 degrees is the top of the ring
+/// let right_side = LedSelection::arc(60., 60.);
+/// assert_eq!(right_side, LedSelection::range(16..=20));
+/// assert_eq!(right_side.mirror(0), LedSelection::range(4..=8));
+/// assert_eq!(LedSelection::every_nth(6, 3), [3, 9, 15, 21].iter().copied().collect());
+///
+/// let both = LedSelection::single(0).or(LedSelection::FUJI);
+/// assert_eq!(both.iter().collect::<Vec<_>>(), vec![0, 24]);
+/// assert!(both.rotate(1).contains(vcs_classic_hid::led::FUJI));
+/// ```
 #[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
 #[repr(transparent)]
-pub struct LedSelection([bool; 24]);
+pub struct LedSelection(u32);
+
+/// The bits used by the LED ring in a selection.
+const RING_MASK: u32 = (1 << 24) - 1;
+
+/// The bit used by the Fuji LED in a selection.
+const FUJI_MASK: u32 = 1 << FUJI;
 
 impl LedSelection {
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Select a single LED by index, from 0 to 23.
+    /// Create a selection from a bit mask,
+    /// where bit `i` selects the LED of index `i`.
+    ///
+    /// Bits beyond the 24 LEDs in the ring and the Fuji LED are ignored.
+    #[inline]
+    pub const fn from_bits(bits: u32) -> Self {
+        LedSelection(bits & (RING_MASK | FUJI_MASK))
+    }
+
+    /// Obtain the bit mask of this selection,
+    /// where bit `i` selects the LED of index `i`.
+    #[inline]
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Select a single LED by index,
+    /// from 0 to 23 or [`FUJI`].
     pub fn single(index: u8) -> Self {
-        let mut x = [false; 24];
-        x[index as usize] = true;
-        LedSelection(x)
+        assert!(index <= FUJI, "invalid LED index {}", index);
+        LedSelection(1 << index)
     }
 
     /// Select a single LED by an arbitrary range of indices.
@@ -74,71 +142,252 @@ impl LedSelection {
     where
         R: IntoIterator<Item = u8>,
     {
-        let mut x = [false; 24];
-        for i in range {
-            x[i as usize] = true;
-        }
-        LedSelection(x)
+        range.into_iter().collect()
     }
 
     /// Select a diagonal quadrant of LEDs, from 0 to 3.
     pub fn quadrant(quadrant: u8) -> Self {
         assert!(quadrant < 4);
-        let mut x = [false; 24];
-        let base = quadrant as usize * 6;
-        if quadrant == 3 {
-            x[base..=base + 5].fill(true);
-            x[0] = true;
-        } else {
-            x[base..=base + 6].fill(true);
-        }
-        LedSelection(x)
+        // 7 LEDs, the last one shared with the next quadrant
+        LedSelection(0x7F).rotate(i32::from(quadrant) * 6)
     }
 
     /// Select a span of LEDS comprising the center LED
-    /// plus the adjacent LEDs at the given radius.
+    /// plus `radius` adjacent LEDs on each side.
+    ///
+    /// A radius of 0 selects only the center LED,
+    /// and a radius of 12 or more selects the whole ring.
     pub fn span(center: u8, radius: u8) -> Self {
-        let mut x = [false; 24];
-        let center = center as usize;
-        let radius = radius as usize;
-        for i in 0..radius {
-            if let Some(l) = x.get_mut((center + i).rem_euclid(24)) {
-                *l = true;
-            }
+        assert!(center < 24, "invalid LED index {}", center);
+        if radius >= 12 {
+            return LedSelection::ALL;
         }
-        for i in 1..radius {
-            if let Some(l) = x.get_mut((center as i32 - i as i32).rem_euclid(24) as usize) {
-                *l = true;
-            }
+        let radius = i32::from(radius);
+        LedSelection((1 << (2 * radius + 1)) - 1).rotate(i32::from(center) - radius)
+    }
+
+    /// Select the LEDs within an arc of the ring.
+    ///
+    /// Angles are in degrees, 15 degrees per LED,
+    /// as in the default [`RingMapping`](mapping::RingMapping):
+    /// 0 is the top of the ring (LED 12)
+    /// and positive angles go clockwise.
+    /// The arc starts at `start` and spans `sweep` degrees,
+    /// counter-clockwise if negative.
+    /// An LED is selected if its center lies within the arc.
+    pub fn arc(start: f32, sweep: f32) -> Self {
+        LedSelection::arc_with_mapping(&mapping::RingMapping::default(), start, sweep)
+    }
+
+    /// Select the LEDs within an arc of the ring,
+    /// with angles relative to the given ring mapping.
+    ///
+    /// See [`arc`](LedSelection::arc).
+    pub fn arc_with_mapping(ring: &mapping::RingMapping, start: f32, sweep: f32) -> Self {
+        if sweep.abs() >= 360. {
+            return LedSelection::ALL;
         }
-        LedSelection(x)
+        let (start, sweep) = if sweep < 0. {
+            (start + sweep, -sweep)
+        } else {
+            (start, sweep)
+        };
+        (0..24)
+            .filter(|i| {
+                let angle = (ring.position_to_degrees(f32::from(*i)) - start).rem_euclid(360.);
+                angle <= sweep
+            })
+            .collect()
+    }
+
+    /// Select every `n`-th LED, starting from the LED at `offset`.
+    pub fn every_nth(n: u8, offset: u8) -> Self {
+        assert!(n > 0);
+        (0..24)
+            .filter(|i| (i + 24 - offset % 24).is_multiple_of(n))
+            .collect()
     }
 
     /// Combine (union) with another selection.
+    #[inline]
     pub fn or(self, other: LedSelection) -> Self {
-        let mut x = [false; 24];
-        for (out, (v1, v2)) in x.iter_mut().zip(self.0.iter().zip(other.0.iter())) {
-            *out = *v1 || *v2;
-        }
-        LedSelection(x)
+        LedSelection(self.0 | other.0)
     }
 
     /// Intersect (filter) with another selection.
+    #[inline]
     pub fn and(self, other: LedSelection) -> Self {
-        let mut x = [false; 24];
-        for (out, (v1, v2)) in x.iter_mut().zip(self.0.iter().zip(other.0.iter())) {
-            *out = *v1 && *v2;
-        }
-        LedSelection(x)
+        LedSelection(self.0 & other.0)
+    }
+
+    /// Select the LEDs in exactly one of the two selections.
+    #[inline]
+    pub fn xor(self, other: LedSelection) -> Self {
+        LedSelection(self.0 ^ other.0)
+    }
+
+    /// Select the LEDs in this selection but not in the other.
+    #[inline]
+    pub fn difference(self, other: LedSelection) -> Self {
+        LedSelection(self.0 & !other.0)
+    }
+
+    /// Select all LEDs in the ring not in this selection (complement).
+    /// The Fuji LED is left as is.
+    #[allow(clippy::should_implement_trait)]
+    #[inline]
+    pub fn not(self) -> Self {
+        LedSelection(self.0 ^ RING_MASK)
+    }
+
+    /// Rotate the selection around the ring by `n` positions,
+    /// clockwise if positive.
+    /// The Fuji LED is left as is.
+    pub fn rotate(self, n: i32) -> Self {
+        let n = n.rem_euclid(24) as u32;
+        let ring = self.0 & RING_MASK;
+        LedSelection(((ring << n) | (ring >> (24 - n))) & RING_MASK | (self.0 & FUJI_MASK))
+    }
+
+    /// Mirror the selection about the axis
+    /// going through the LED at `axis` and the LED opposite to it.
+    /// The Fuji LED is left as is.
+    pub fn mirror(self, axis: u8) -> Self {
+        let axis = i32::from(axis);
+        let ring: LedSelection = self
+            .and(LedSelection::ALL)
+            .iter()
+            .map(|i| (2 * axis - i32::from(i)).rem_euclid(24) as u8)
+            .collect();
+        LedSelection(ring.0 | (self.0 & FUJI_MASK))
+    }
+
+    /// Whether the LED of the given index
+    /// (from 0 to 23 or [`FUJI`]) is selected.
+    #[inline]
+    pub fn contains(self, led: u8) -> bool {
+        led <= FUJI && (self.0 >> led) & 1 == 1
     }
-    
-    /// Select all LEDs.
-    pub const ALL: LedSelection = LedSelection([true; 24]);
+
+    /// Whether the Fuji LED is selected.
+    #[inline]
+    pub fn contains_fuji(self) -> bool {
+        self.contains(FUJI)
+    }
+
+    /// The number of LEDs selected.
+    #[inline]
+    pub fn count(self) -> u8 {
+        self.0.count_ones() as u8
+    }
+
+    /// Whether no LED is selected.
+    #[inline]
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Iterate over the indices of the selected LEDs in increasing order,
+    /// ending with [`FUJI`] if the Fuji LED is selected.
+    #[inline]
+    pub fn iter(self) -> LedSelectionIter {
+        LedSelectionIter(self.0)
+    }
+
+    /// Select all LEDs in the ring.
+    pub const ALL: LedSelection = LedSelection(RING_MASK);
+
+    /// Select the Fuji LED.
+    pub const FUJI: LedSelection = LedSelection(FUJI_MASK);
+
+    /// Select all LEDs in the ring as well as the Fuji LED.
+    pub const ALL_WITH_FUJI: LedSelection = LedSelection(RING_MASK | FUJI_MASK);
 
     /// Select no LED.
-    pub const NONE: LedSelection = LedSelection([false; 24]);
+    pub const NONE: LedSelection = LedSelection(0);
+}
+
+impl std::iter::FromIterator<u8> for LedSelection {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        iter.into_iter()
+            .fold(LedSelection::NONE, |x, i| x.or(LedSelection::single(i)))
+    }
+}
+
+impl IntoIterator for LedSelection {
+    type Item = u8;
+    type IntoIter = LedSelectionIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
 }
 
+impl std::ops::BitOr for LedSelection {
+    type Output = LedSelection;
+
+    #[inline]
+    fn bitor(self, rhs: LedSelection) -> Self::Output {
+        self.or(rhs)
+    }
+}
+
+impl std::ops::BitAnd for LedSelection {
+    type Output = LedSelection;
+
+    #[inline]
+    fn bitand(self, rhs: LedSelection) -> Self::Output {
+        self.and(rhs)
+    }
+}
+
+impl std::ops::BitXor for LedSelection {
+    type Output = LedSelection;
+
+    #[inline]
+    fn bitxor(self, rhs: LedSelection) -> Self::Output {
+        self.xor(rhs)
+    }
+}
+
+impl std::ops::Not for LedSelection {
+    type Output = LedSelection;
+
+    #[inline]
+    fn not(self) -> Self::Output {
+        LedSelection::not(self)
+    }
+}
+
+/// Iterator over the indices of the LEDs in a [`LedSelection`].
+#[derive(Debug, Clone)]
+pub struct LedSelectionIter(u32);
+
+impl Iterator for LedSelectionIter {
+    type Item = u8;
+
+    #[inline]
+    fn next(&mut self) -> Option<u8> {
+        if self.0 == 0 {
+            return None;
+        }
+        let i = self.0.trailing_zeros();
+        self.0 &= self.0 - 1;
+        Some(i as u8)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.0.count_ones() as usize;
+        (n, Some(n))
+    }
+}
+
+impl ExactSizeIterator for LedSelectionIter {}
+
 /// Structure representing a report for LED activation on the controller.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct LedReport([u8; 28]);
@@ -189,65 +438,183 @@ impl LedReport {
         self.0[2] = value;
     }
 
-    /// Set a LED in the ring to a value.
+    /// Set a LED in the ring (or the Fuji LED via [`FUJI`]) to a value.
     #[inline]
     pub fn set(&mut self, led: u8, value: u8) {
-        self.0[3 + led as usize] = value;
+        self.0[Self::offset(led)] = value;
     }
 
-    /// Set a selection of LEDs in the ring to a value.
+    /// Set a selection of LEDs to a value.
     #[inline]
     pub fn set_selection(&mut self, selection: LedSelection, value: u8) {
-        for (led, sel) in std::iter::Iterator::zip(self.0.iter_mut().skip(3), selection.0.iter()) {
-            if *sel {
-                *led = value;
-            }
+        for i in selection.iter() {
+            self.set(i, value);
         }
     }
 
-    /// Invert the value of the LED in the ring.
+    /// Invert the value of the LED in the ring (or the Fuji LED via [`FUJI`]).
     #[inline]
     pub fn invert(&mut self, led: u8) {
-        let led = led as usize;
-        self.0[3 + led] = !self.0[3 + led];
+        let offset = Self::offset(led);
+        self.0[offset] = !self.0[offset];
     }
 
-    /// Invert the values of a selection of the LED in the ring.
+    /// Invert the values of a selection of LEDs.
     #[inline]
     pub fn invert_selection(&mut self, selection: LedSelection) {
-        for (led, sel) in std::iter::Iterator::zip(self.0.iter_mut().skip(3), selection.0.iter()) {
-            if *sel {
-                *led = !*led;
-            }
+        for i in selection.iter() {
+            self.invert(i);
         }
     }
 
-    /// Add an intensity to a LED in the ring.
+    /// Add an intensity to a LED in the ring (or the Fuji LED via [`FUJI`]).
     ///
     /// The addition is relative to the receiving report value,
     /// and not the current state of the LEDs in the controller.
     /// Values are automatically clamped to the limits of the device.
     #[inline]
     pub fn saturating_add(&mut self, led: u8, value_delta: i16) {
-        let led = led as usize;
-        let current = self.0[3 + led];
+        let offset = Self::offset(led);
+        let current = self.0[offset];
         let out = (i16::from(current) + value_delta).clamp(0, 255);
-        self.0[3 + led] = out as u8;
+        self.0[offset] = out as u8;
     }
 
-    /// Add an intensity to a selection of LEDs in the ring.
+    /// Add an intensity to a selection of LEDs.
     ///
     /// The addition is relative to the receiving report value,
     /// and not the current state of the LEDs in the controller.
     /// Values are automatically clamped to the limits of the device.
     #[inline]
     pub fn saturating_add_selection(&mut self, selection: LedSelection, value_delta: i16) {
-        selection
-            .0
-            .iter()
-            .enumerate()
-            .filter_map(|(i, sel)| if *sel { Some(i) } else { None })
-            .for_each(|i| self.saturating_add(i as u8, value_delta))
+        for i in selection.iter() {
+            self.saturating_add(i, value_delta);
+        }
+    }
+
+    /// Get the value of the Fuji LED.
+    #[inline]
+    pub fn fuji(&self) -> u8 {
+        self.0[2]
+    }
+
+    /// Get the value of a LED in the ring (or the Fuji LED via [`FUJI`]).
+    #[inline]
+    pub fn get(&self, led: u8) -> u8 {
+        self.0[Self::offset(led)]
+    }
+
+    /// The position in the report of the given LED.
+    #[inline]
+    fn offset(led: u8) -> usize {
+        match led {
+            FUJI => 2,
+            led => {
+                assert!(led < 24, "invalid LED index {}", led);
+                3 + led as usize
+            }
+        }
+    }
+
+    /// Get the values of all LEDs in the ring.
+    #[inline]
+    pub fn ring(&self) -> [u8; 24] {
+        let mut out = [0; 24];
+        out.copy_from_slice(&self.0[3..27]);
+        out
+    }
+
+    /// Linearly interpolate between two reports,
+    /// including the Fuji LED.
+    ///
+    /// A `t` of 0 results in `from`, whereas a `t` of 1 results in `to`.
+    /// Moving `t` from 0 to 1 over time crossfades between the two.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use vcs_classic_hid::LedReport;
+    ///
+    /// let from = LedReport::filled(0x00);
+    /// let to = LedReport::filled(0xC0);
+    /// assert_eq!(LedReport::lerp(&from, &to, 0.25).get(5), 0x30);
+    /// ```
+    pub fn lerp(from: &LedReport, to: &LedReport, t: f32) -> LedReport {
+        let t = t.clamp(0., 1.);
+        let mut out = *from;
+        for (o, v) in out.0[2..27].iter_mut().zip(&to.0[2..27]) {
+            let a = f32::from(*o);
+            let b = f32::from(*v);
+            *o = (a + (b - a) * t).round() as u8;
+        }
+        out
+    }
+
+    /// Rotate the ring of LEDs by `n` positions,
+    /// clockwise if positive.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use vcs_classic_hid::LedReport;
+    ///
+    /// let mut report = LedReport::new();
+    /// report.set(23, 0xFF);
+    /// report.rotate(2);
+    /// assert_eq!(report.get(1), 0xFF);
+    /// assert_eq!(report.get(23), 0);
+    /// ```
+    pub fn rotate(&mut self, n: i32) {
+        let n = n.rem_euclid(24) as usize;
+        self.0[3..27].rotate_right(n);
+    }
+
+    /// Mirror the ring of LEDs about the axis
+    /// going through the LED at `axis` and the LED opposite to it.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use vcs_classic_hid::LedReport;
+    ///
+    /// let mut report = LedReport::new();
+    /// report.set(9, 0xFF);
+    /// report.mirror(12);
+    /// assert_eq!(report.get(15), 0xFF);
+    /// ```
+    pub fn mirror(&mut self, axis: u8) {
+        let ring = self.ring();
+        let axis = i32::from(axis);
+        for (i, value) in ring.iter().enumerate() {
+            let j = (2 * axis - i as i32).rem_euclid(24) as u8;
+            self.set(j, *value);
+        }
+    }
+
+    /// Scale the intensity of all LEDs by the given factor,
+    /// including the Fuji LED.
+    ///
+    /// Values are automatically clamped to the limits of the device.
+    pub fn scale(&mut self, factor: f32) {
+        for v in &mut self.0[2..27] {
+            *v = (f32::from(*v) * factor).round().clamp(0., 255.) as u8;
+        }
+    }
+
+    /// Merge with another report,
+    /// keeping the highest intensity of each LED.
+    pub fn max(&mut self, other: &LedReport) {
+        for (v, o) in self.0[2..27].iter_mut().zip(&other.0[2..27]) {
+            *v = (*v).max(*o);
+        }
+    }
+
+    /// Merge with another report,
+    /// keeping the lowest intensity of each LED.
+    pub fn min(&mut self, other: &LedReport) {
+        for (v, o) in self.0[2..27].iter_mut().zip(&other.0[2..27]) {
+            *v = (*v).min(*o);
+        }
     }
 
     /// Send this report as an HID message to the given device.
@@ -0,0 +1,308 @@
+//! Priority based scheduling of LED animations
+//!
+//! When the LEDs are used as a status display,
+//! transient notifications (such as a flash on error)
+//! need to temporarily override the ambient animation,
+//! which should then resume as if nothing happened.
+//!
+//! A [`Scheduler`] renders a background animation,
+//! over which foreground animations are shown one at a time,
+//! picked by priority.
+//! A foreground animation is shown until it reports
+//! [`AnimationEvent::Ended`] or its duration elapses.
+//! The scheduler is itself an [`LedAnimation`],
+//! and so can be rendered by an [`LedDriver`](super::driver::LedDriver).
+//!
+//! ## Example
+//!
+//! ```
+//! use vcs_classic_hid::led::{
+//!     anims::{Asr, Still},
+//!     scheduler::{Preemption, Scheduler},
+//!     LedAnimation, LedReport, LedSelection,
+//! };
+//!
+//! let mut scheduler = Scheduler::new();
+//! scheduler.set_background(Still::new(LedReport::filled(0x10)));
+//! scheduler.enqueue(Still::new(LedReport::filled(0xFF)), 1, Some(10), Preemption::Preempt);
+//!
+//! let mut report = LedReport::new();
+//! scheduler.update(0, &mut report);
+//! assert_eq!(report.get(0), 0xFF);
+//!
+//! // the notification is over, back to the background
+//! let mut report = LedReport::new();
+//! scheduler.update(10, &mut report);
+//! assert_eq!(report.get(0), 0x10);
+//! ```
+use super::{AnimationEvent, LedAnimation, LedReport};
+
+/// Unique identifier of an animation enqueued in a [`Scheduler`].
+#[derive(Debug, Copy, Clone, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct AnimationId(u64);
+
+/// How a foreground animation competes with the one currently shown.
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum Preemption {
+    /// Interrupt the current animation if it has a lower priority.
+    /// The interrupted animation resumes once this one is over.
+    Preempt,
+    /// Wait for the current animation to be over before showing.
+    #[default]
+    Queue,
+    /// Discard the animation
+    /// if another foreground animation is already being shown.
+    Skip,
+}
+
+struct Entry {
+    id: AnimationId,
+    priority: u8,
+    duration: Option<u64>,
+    preemption: Preemption,
+    animation: Box<dyn LedAnimation + Send>,
+    /// the local tick at which the animation started
+    started: Option<u64>,
+    /// ticks spent paused, subtracted from the global ticks
+    offset: u64,
+    /// the global tick at which the animation was interrupted
+    paused_at: Option<u64>,
+}
+
+impl std::fmt::Debug for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Entry")
+            .field("id", &self.id)
+            .field("priority", &self.priority)
+            .field("duration", &self.duration)
+            .field("preemption", &self.preemption)
+            .field("started", &self.started)
+            .field("offset", &self.offset)
+            .field("paused_at", &self.paused_at)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A scheduler of LED animations,
+/// with a background animation and prioritized foreground animations.
+pub struct Scheduler {
+    background: Option<Box<dyn LedAnimation + Send>>,
+    background_fresh: bool,
+    /// pending and interrupted foreground animations
+    queue: Vec<Entry>,
+    /// the foreground animation being shown
+    active: Option<Entry>,
+    next_id: u64,
+}
+
+impl std::fmt::Debug for Scheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Scheduler")
+            .field("background", &self.background.is_some())
+            .field("queue", &self.queue)
+            .field("active", &self.active)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Scheduler::new()
+    }
+}
+
+impl Scheduler {
+    /// Create a scheduler with no animations.
+    pub fn new() -> Self {
+        Scheduler {
+            background: None,
+            background_fresh: false,
+            queue: Vec::new(),
+            active: None,
+            next_id: 0,
+        }
+    }
+
+    /// Replace the background animation.
+    ///
+    /// The animation is reset on the next update.
+    pub fn set_background<A>(&mut self, animation: A)
+    where
+        A: LedAnimation + Send + 'static,
+    {
+        self.background = Some(Box::new(animation));
+        self.background_fresh = true;
+    }
+
+    /// Remove the background animation.
+    pub fn clear_background(&mut self) {
+        self.background = None;
+    }
+
+    /// Enqueue a foreground animation.
+    ///
+    /// - `priority`: animations of higher priority are shown first
+    /// - `duration`: the maximum number of ticks to show the animation,
+    ///   or `None` to show it until it ends
+    /// - `preemption`: how to compete with the animation currently shown
+    ///
+    /// Animations of the same priority are shown in the order enqueued.
+    /// Returns `None` if the animation was discarded.
+    pub fn enqueue<A>(
+        &mut self,
+        animation: A,
+        priority: u8,
+        duration: Option<u64>,
+        preemption: Preemption,
+    ) -> Option<AnimationId>
+    where
+        A: LedAnimation + Send + 'static,
+    {
+        if preemption == Preemption::Skip && self.active.is_some() {
+            return None;
+        }
+        let id = AnimationId(self.next_id);
+        self.next_id += 1;
+        self.queue.push(Entry {
+            id,
+            priority,
+            duration,
+            preemption,
+            animation: Box::new(animation),
+            started: None,
+            offset: 0,
+            paused_at: None,
+        });
+        Some(id)
+    }
+
+    /// Remove a foreground animation, whether shown or pending.
+    ///
+    /// Returns whether the animation was found.
+    pub fn cancel(&mut self, id: AnimationId) -> bool {
+        if self.active.as_ref().map(|e| e.id) == Some(id) {
+            self.active = None;
+            return true;
+        }
+        let len = self.queue.len();
+        self.queue.retain(|e| e.id != id);
+        self.queue.len() != len
+    }
+
+    /// Remove all foreground animations.
+    pub fn clear(&mut self) {
+        self.active = None;
+        self.queue.clear();
+    }
+
+    /// The foreground animation currently shown, if any.
+    pub fn active(&self) -> Option<AnimationId> {
+        self.active.as_ref().map(|e| e.id)
+    }
+
+    /// Whether there are no foreground animations, shown or pending.
+    pub fn is_idle(&self) -> bool {
+        self.active.is_none() && self.queue.is_empty()
+    }
+
+    /// Index of the next animation to show from the queue:
+    /// highest priority first, then the earliest enqueued.
+    fn next_in_queue(&self) -> Option<usize> {
+        self.queue
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.priority.cmp(&b.priority).then(b.id.cmp(&a.id)))
+            .map(|(i, _)| i)
+    }
+
+    fn schedule(&mut self, ticks: u64) {
+        let next = match self.next_in_queue() {
+            Some(next) => next,
+            None => return,
+        };
+        if let Some(active) = &self.active {
+            let candidate = &self.queue[next];
+            if candidate.preemption != Preemption::Preempt || candidate.priority <= active.priority {
+                return;
+            }
+        }
+
+        let mut entry = self.queue.remove(next);
+        if let Some(paused_at) = entry.paused_at.take() {
+            entry.offset += ticks.saturating_sub(paused_at);
+        }
+        if let Some(mut interrupted) = self.active.take() {
+            interrupted.paused_at = Some(ticks);
+            self.queue.push(interrupted);
+        }
+        self.active = Some(entry);
+    }
+}
+
+impl Entry {
+    /// Forget the progress of the animation,
+    /// so that it starts over the next time it is shown.
+    fn rewind(&mut self) {
+        self.started = None;
+        self.offset = 0;
+        self.paused_at = None;
+    }
+}
+
+impl LedAnimation for Scheduler {
+    /// Rewind the background animation
+    /// and the foreground animations, shown or pending.
+    /// Foreground animations start over with their full duration.
+    fn reset(&mut self, ticks: u64) {
+        if let Some(background) = &mut self.background {
+            background.reset(ticks);
+        }
+        self.background_fresh = false;
+        for entry in self.active.iter_mut().chain(&mut self.queue) {
+            entry.rewind();
+        }
+    }
+
+    fn update(&mut self, ticks: u64, report: &mut LedReport) -> AnimationEvent {
+        if let Some(background) = &mut self.background {
+            if self.background_fresh {
+                background.reset(ticks);
+                self.background_fresh = false;
+            }
+            background.update(ticks, report);
+        }
+
+        // show the next foreground animation which has not ended yet
+        loop {
+            self.schedule(ticks);
+            let entry = match &mut self.active {
+                Some(entry) => entry,
+                None => break,
+            };
+
+            let local = ticks.saturating_sub(entry.offset);
+            let started = match entry.started {
+                Some(started) => started,
+                None => {
+                    entry.animation.reset(local);
+                    entry.started = Some(local);
+                    local
+                }
+            };
+            let expired = entry
+                .duration
+                .map(|d| local.saturating_sub(started) >= d)
+                .unwrap_or(false);
+            if !expired {
+                let mut frame = *report;
+                if entry.animation.update(local, &mut frame) == AnimationEvent::Running {
+                    *report = frame;
+                    break;
+                }
+            }
+            self.active = None;
+        }
+
+        AnimationEvent::Running
+    }
+}
@@ -0,0 +1,156 @@
+//! Transitions between LED animations
+//!
+//! Switching from one animation to another
+//! makes the LEDs change abruptly from one frame to the next.
+//! A [`Transition`] blends an outgoing animation into an incoming one
+//! over a given number of ticks, according to a [`TransitionStyle`].
+//!
+//! ## Example
+//!
+//! ```
+//! use vcs_classic_hid::led::{
+//!     anims::Still,
+//!     transition::{Transition, TransitionStyle},
+//!     LedAnimation, LedReport,
+//! };
+//!
+//! let mut transition = Transition::new(
+//!     Still::new(LedReport::filled(0xFF)),
+//!     Still::new(LedReport::filled(0)),
+//!     TransitionStyle::Wipe { start: 0 },
+//!     24,
+//! );
+//! transition.reset(0);
+//! let mut report = LedReport::new();
+//! transition.update(12, &mut report);
+//! // the first half of the ring has been swept
+//! assert_eq!(report.get(11), 0);
+//! assert_eq!(report.get(12), 0xFF);
+//! ```
+use super::{AnimationEvent, LedAnimation, LedReport};
+
+/// The manner in which one animation gives way to the other.
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum TransitionStyle {
+    /// Fade out the outgoing animation while fading in the incoming one.
+    #[default]
+    Crossfade,
+    /// Sweep the incoming animation over the outgoing one,
+    /// clockwise from the `start` LED.
+    Wipe { start: u8 },
+    /// Expand the incoming animation from the `center` LED
+    /// to both sides of the ring.
+    Iris { center: u8 },
+}
+
+/// An animation blending from an outgoing animation into an incoming one.
+///
+/// The outgoing animation keeps running during the transition.
+/// The incoming animation is reset when the transition is reset,
+/// and continues to be shown once the transition is over.
+/// If the incoming animation ends during the transition,
+/// the transition ends along with it once over.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transition<A, B> {
+    outgoing: A,
+    incoming: B,
+    style: TransitionStyle,
+    duration: u64,
+    base_tick: u64,
+    /// the last frame of the incoming animation,
+    /// if it ended during the transition
+    incoming_end: Option<LedReport>,
+}
+
+impl<A, B> Transition<A, B>
+where
+    A: LedAnimation,
+    B: LedAnimation,
+{
+    /// Create a transition lasting `duration` ticks.
+    pub fn new(outgoing: A, incoming: B, style: TransitionStyle, duration: u64) -> Self {
+        Transition {
+            outgoing,
+            incoming,
+            style,
+            duration,
+            base_tick: 0,
+            incoming_end: None,
+        }
+    }
+
+    /// Whether the transition is over,
+    /// so that only the incoming animation is shown.
+    pub fn is_done(&self, ticks: u64) -> bool {
+        ticks.saturating_sub(self.base_tick) >= self.duration
+    }
+
+    /// Retrieve the incoming animation,
+    /// usually once the transition is over.
+    pub fn into_incoming(self) -> B {
+        self.incoming
+    }
+
+    /// The weight of the incoming animation on the given LED,
+    /// with `t` as the progress of the transition.
+    fn weight(&self, led: u8, t: f32) -> f32 {
+        match self.style {
+            TransitionStyle::Crossfade => t,
+            TransitionStyle::Wipe { start } => {
+                let distance = f32::from((led + 24 - start % 24) % 24);
+                (t * 24. - distance).clamp(0., 1.)
+            }
+            TransitionStyle::Iris { center } => {
+                let d = (i16::from(led) - i16::from(center)).rem_euclid(24);
+                let distance = f32::from(d.min(24 - d));
+                (t * 13. - distance).clamp(0., 1.)
+            }
+        }
+    }
+}
+
+impl<A, B> LedAnimation for Transition<A, B>
+where
+    A: LedAnimation,
+    B: LedAnimation,
+{
+    fn reset(&mut self, ticks: u64) {
+        self.base_tick = ticks;
+        self.incoming_end = None;
+        self.incoming.reset(ticks);
+    }
+
+    fn update(&mut self, ticks: u64, report: &mut LedReport) -> AnimationEvent {
+        if self.is_done(ticks) {
+            if let Some(end) = self.incoming_end {
+                *report = end;
+                return AnimationEvent::Ended;
+            }
+            return self.incoming.update(ticks, report);
+        }
+
+        let t = ticks.saturating_sub(self.base_tick) as f32 / self.duration as f32;
+
+        let mut from = *report;
+        self.outgoing.update(ticks, &mut from);
+        let to = match self.incoming_end {
+            Some(end) => end,
+            None => {
+                let mut to = *report;
+                if self.incoming.update(ticks, &mut to) == AnimationEvent::Ended {
+                    self.incoming_end = Some(to);
+                }
+                to
+            }
+        };
+
+        report.set_fuji(LedReport::lerp(&from, &to, t).fuji());
+        for led in 0..24 {
+            let w = self.weight(led, t);
+            let a = f32::from(from.get(led));
+            let b = f32::from(to.get(led));
+            report.set(led, (a + (b - a) * w).round() as u8);
+        }
+        AnimationEvent::Running
+    }
+}
@@ -90,14 +90,23 @@
 //! # }
 //! ```
 //!
+//! ## Writing games
+//!
+//! The [`game`] module provides a simple game loop
+//! and clocks for running it in real time or in tests.
+//! The [`rand`] module provides a small seedable random number generator
+//! for reproducible effects and test input.
+//!
 use std::ffi::CStr;
 
 pub use hidapi;
 use hidapi::{HidApi, HidDevice};
 
 pub mod force_feedback;
+pub mod game;
 pub mod led;
 pub mod input;
+pub mod rand;
 
 pub use force_feedback::FfReport;
 pub use led::LedReport;
@@ -180,8 +189,10 @@ impl Device for HidDevice {
     }
 }
 
-const VENDOR_ID: u16 = 0x3250;
-const PRODUCT_ID: u16 = 0x1001;
+/// The USB vendor ID of the VCS classic controller
+pub const VENDOR_ID: u16 = 0x3250;
+/// The USB product ID of the VCS classic controller
+pub const PRODUCT_ID: u16 = 0x1001;
 
 /// Inspect the list of devices available
 /// and open the first VCS classic controller device found.
@@ -0,0 +1,50 @@
+//! Seedable pseudo-random number generation.
+//!
+//! Random LED effects and test input need randomness which
+//! can be reproduced from a seed on every platform,
+//! without the weight of a general purpose random number crate.
+//! The generators here are fast but not cryptographically secure.
+
+/// A minimal xorshift pseudo-random number generator
+/// with 32 bits of state.
+///
+/// The same seed always produces the same sequence of numbers.
+///
+/// ## Example
+///
+/// ```
+/// use vcs_classic_hid::rand::XorShift32;
+///
+/// let mut a = XorShift32::new(42);
+/// let mut b = XorShift32::new(42);
+/// assert_eq!(a.next_u32(), b.next_u32());
+/// assert_eq!(a.next_u8(), b.next_u8());
+/// ```
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub struct XorShift32(u32);
+
+impl XorShift32 {
+    /// Create a generator from the given seed.
+    ///
+    /// Since the all-zeros state would only produce zeros,
+    /// a seed of 0 is replaced by a fixed non-zero value.
+    pub fn new(seed: u32) -> Self {
+        XorShift32(if seed == 0 { 0x9E37_79B9 } else { seed })
+    }
+
+    /// Obtain the next 32-bit number.
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.0 = x;
+        x
+    }
+
+    /// Obtain the next 8-bit number,
+    /// taken from the high bits of the next 32-bit number.
+    pub fn next_u8(&mut self) -> u8 {
+        (self.next_u32() >> 24) as u8
+    }
+}
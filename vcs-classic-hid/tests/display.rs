@@ -0,0 +1,57 @@
+//! Numbers shown on the LED ring.
+use vcs_classic_hid::led::{
+    display::{NumberDisplay, NumberStyle},
+    LedAnimation, LedReport,
+};
+
+/// Render a number over a fully lit report.
+fn render(value: u32, style: NumberStyle) -> [u8; 24] {
+    let mut display = NumberDisplay::new_with_params(value, style, 0xFF, 0x20);
+    let mut report = LedReport::filled(0x80);
+    display.update(0, &mut report);
+    report.ring()
+}
+
+/// The ring in display order, starting from the top.
+fn from_top(ring: [u8; 24]) -> Vec<u8> {
+    (0..24).map(|i| ring[(12 + i) % 24]).collect()
+}
+
+#[test]
+fn binary_saturates() {
+    let ring = render(1 << 24, NumberStyle::Binary);
+    assert_eq!(ring, [0xFF; 24]);
+    let ring = render(u32::MAX, NumberStyle::Binary);
+    assert_eq!(ring, [0xFF; 24]);
+
+    let ring = render((1 << 24) - 2, NumberStyle::Binary);
+    let mut expected = vec![0xFF; 24];
+    expected[0] = 0x20;
+    assert_eq!(from_top(ring), expected);
+}
+
+#[test]
+fn digits_clear_leftover_leds() {
+    // 4 digits of 5 LEDs, with 4 LEDs left over
+    let ring = from_top(render(7, NumberStyle::Digits { base: 5 }));
+    assert_eq!(
+        ring,
+        vec![
+            0x20, 0, 0, 0, 0, // 0
+            0x20, 0, 0, 0, 0, // 0
+            0x20, 0xFF, 0, 0, 0, // 1
+            0x20, 0xFF, 0xFF, 0, 0, // 2
+            0, 0, 0, 0,
+        ]
+    );
+}
+
+#[test]
+fn tally_clears_leds_past_the_marks() {
+    let ring = from_top(render(6, NumberStyle::Tally));
+    // two thirds of the intensity
+    let regular = 0xAA;
+    let mut expected = vec![regular, regular, regular, regular, 0xFF, regular];
+    expected.resize(24, 0);
+    assert_eq!(ring, expected);
+}
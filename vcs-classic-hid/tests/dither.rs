@@ -0,0 +1,87 @@
+//! Dithering of high precision LED animations.
+use vcs_classic_hid::led::{
+    anims::{Asr, Pulsate},
+    dither::{Dithered, HdLedAnimation, HdLedReport},
+    LedAnimation, LedReport, LedSelection,
+};
+
+/// Render the value of the first LED over a range of ticks.
+fn render<A: LedAnimation>(animation: &mut A, ticks: std::ops::Range<u64>) -> Vec<u8> {
+    ticks
+        .map(|ticks| {
+            let mut report = LedReport::new();
+            animation.update(ticks, &mut report);
+            report.get(0)
+        })
+        .collect()
+}
+
+fn mean(values: &[u8]) -> f64 {
+    values.iter().map(|v| f64::from(*v)).sum::<f64>() / values.len() as f64
+}
+
+#[test]
+fn slow_release_dithers_between_adjacent_levels() {
+    // one intensity level every 100 ticks
+    let asr = Asr::new_with_params(LedSelection::ALL, 0xFF, 0, 0, 25_500);
+    let mut plain = asr;
+    plain.reset(0);
+    let mut dithered = Dithered::new(asr);
+    dithered.reset(0);
+
+    // from level 5 down to level 4
+    let ticks = 25_000..25_100;
+    let stepped = render(&mut plain, ticks.clone());
+    let values = render(&mut dithered, ticks);
+
+    // without dithering, the fade is stuck on one level
+    assert!(stepped.iter().all(|v| *v == stepped[0]));
+
+    assert!(values.iter().all(|v| *v == 4 || *v == 5), "{:?}", values);
+    let changes = values.windows(2).filter(|w| w[0] != w[1]).count();
+    assert!(changes > 10, "{:?}", values);
+    assert!((mean(&values) - 4.5).abs() < 0.1, "{:?}", values);
+
+    // later on, the fade goes below the first level
+    let values = render(&mut dithered, 25_400..25_500);
+    assert!(values.iter().all(|v| *v <= 1), "{:?}", values);
+    assert!((mean(&values) - 0.5).abs() < 0.1, "{:?}", values);
+}
+
+#[test]
+fn pulsate_in_high_precision() {
+    let mut pulsate = Pulsate::new_with_params(LedSelection::ALL, 1000, 0, 4);
+    let mut frame = HdLedReport::new();
+    pulsate.update_hd(0, &mut frame);
+    assert_eq!(frame.get(0), 4 * 0x101);
+    pulsate.update_hd(500, &mut frame);
+    assert_eq!(frame.get(0), 2 * 0x101);
+    pulsate.update_hd(1500, &mut frame);
+    assert_eq!(frame.get(0), 2 * 0x101);
+
+    // matches the regular rendering at each level
+    let mut report = LedReport::new();
+    for ticks in (0..2000).step_by(250) {
+        pulsate.update(ticks, &mut report);
+        pulsate.update_hd(ticks, &mut frame);
+        assert_eq!(frame.to_report().get(0), report.get(0), "tick {}", ticks);
+    }
+}
+
+#[test]
+fn untouched_leds_are_kept() {
+    let mut dithered = Dithered::new(Pulsate::new_with_params(
+        LedSelection::single(0),
+        1000,
+        0,
+        4,
+    ));
+    for ticks in 0..100 {
+        let mut report = LedReport::filled(0x33);
+        report.set_fuji(0x77);
+        dithered.update(ticks, &mut report);
+        assert_eq!(report.get(1), 0x33);
+        assert_eq!(report.get(23), 0x33);
+        assert_eq!(report.fuji(), 0x77);
+    }
+}
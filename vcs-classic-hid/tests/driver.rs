@@ -0,0 +1,84 @@
+//! Background LED rendering against a recording device.
+use std::time::{Duration, Instant};
+
+use vcs_classic_hid::{
+    led::{
+        driver::{LedDriver, LedDriverConfig},
+        AnimationEvent, LedAnimation, LedReport,
+    },
+    Device,
+};
+
+/// A device which keeps every report written to it
+#[derive(Debug, Default)]
+struct Recorder {
+    written: Vec<Vec<u8>>,
+}
+
+impl Device for Recorder {
+    type Error = ();
+
+    fn set_blocking(&mut self, _blocking: bool) -> Result<(), ()> {
+        Ok(())
+    }
+
+    fn read(&mut self, _out: &mut [u8]) -> Result<usize, ()> {
+        Ok(0)
+    }
+
+    fn write<T>(&mut self, data: T) -> Result<usize, ()>
+    where
+        T: AsRef<[u8]>,
+    {
+        self.written.push(data.as_ref().to_vec());
+        Ok(data.as_ref().len())
+    }
+}
+
+/// Lights LED 0 with the tick number, ending at the given tick.
+struct Countdown(u64);
+
+impl LedAnimation for Countdown {
+    fn reset(&mut self, _ticks: u64) {}
+
+    fn update(&mut self, ticks: u64, report: &mut LedReport) -> AnimationEvent {
+        report.set(0, ticks as u8);
+        if ticks >= self.0 {
+            AnimationEvent::Ended
+        } else {
+            AnimationEvent::Running
+        }
+    }
+}
+
+#[test]
+fn last_frame_is_sent_when_throttled() {
+    let config = LedDriverConfig {
+        fps: 1000,
+        max_send_rate: 10,
+        skip_unchanged: true,
+    };
+    let driver = LedDriver::spawn(Recorder::default(), Countdown(3), config);
+    let start = Instant::now();
+    while !driver.is_finished() {
+        assert!(
+            start.elapsed() < Duration::from_secs(5),
+            "driver did not finish"
+        );
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    let stats = driver.stats();
+    let device = driver.stop().unwrap();
+
+    // the first frame, then the last one in spite of the send rate
+    let mut first = LedReport::new();
+    first.set(0, 0);
+    let mut last = LedReport::new();
+    last.set(0, 3);
+    assert_eq!(
+        device.written,
+        vec![first.as_ref().to_vec(), last.as_ref().to_vec()]
+    );
+    assert_eq!(stats.sent, 2);
+    assert!(stats.throttled >= 1);
+}
@@ -0,0 +1,139 @@
+//! Force feedback limits across several reports.
+use std::time::Duration;
+
+use vcs_classic_hid::force_feedback::{
+    limiter::{Adjustment, FfLimiter, FfLimits, FfOutcome, Rejection},
+    FfReport,
+};
+
+fn limiter() -> FfLimiter {
+    FfLimiter::new(FfLimits {
+        max_on_time: Duration::from_secs(2),
+        duty_cycle: 1.,
+        window: Duration::from_secs(60),
+        ..FfLimits::default()
+    })
+}
+
+/// A continuous vibration of 1.5 seconds
+fn constant() -> FfReport {
+    FfReport::new_with_params(0xFF, 50, 0, 3)
+}
+
+#[test]
+fn back_to_back_reports_are_one_vibration() {
+    let mut limiter = limiter();
+    let first = limiter.admit(constant(), Duration::from_secs(0)).unwrap();
+    assert_eq!(first, FfOutcome::Accepted(constant()));
+
+    // sent before the first one ends, so only 1 second is left
+    let second = limiter
+        .admit(constant(), Duration::from_millis(1000))
+        .unwrap();
+    assert_eq!(
+        second,
+        FfOutcome::Clamped {
+            report: FfReport::new_with_params(0xFF, 50, 0, 2),
+            adjustments: vec![Adjustment::OnTime],
+        }
+    );
+    assert_eq!(
+        limiter.on_time(Duration::from_secs(2)),
+        Duration::from_secs(2)
+    );
+
+    // the vibration has lasted the maximum continuous time
+    assert_eq!(
+        limiter.admit(constant(), Duration::from_secs(2)),
+        Err(Rejection::OnTimeExceeded)
+    );
+
+    // after a pause, the device may vibrate again
+    let third = limiter
+        .admit(constant(), Duration::from_millis(2100))
+        .unwrap();
+    assert_eq!(third, FfOutcome::Accepted(constant()));
+}
+
+#[test]
+fn pulses_are_not_continuous() {
+    let mut limiter = limiter();
+    let pulses = FfReport::new_with_params(0xFF, 50, 10, 3);
+    for i in 0..4 {
+        let outcome = limiter.admit(pulses, pulses.duration() * i).unwrap();
+        assert_eq!(outcome, FfOutcome::Accepted(pulses));
+    }
+    assert_eq!(
+        limiter.on_time(pulses.duration() * 4),
+        Duration::from_secs(0)
+    );
+}
+
+#[test]
+fn stop_then_resend() {
+    let mut limiter = limiter();
+    limiter.admit(constant(), Duration::from_secs(0)).unwrap();
+    let stop = limiter
+        .admit(FfReport::new(), Duration::from_millis(1000))
+        .unwrap();
+    assert_eq!(stop, FfOutcome::Accepted(FfReport::new()));
+    // the motor is off, the rest of the vibration never happened
+    assert_eq!(
+        limiter.on_time(Duration::from_millis(1000)),
+        Duration::from_secs(0)
+    );
+    assert_eq!(
+        limiter.used(Duration::from_millis(1000)),
+        Duration::from_secs(1)
+    );
+
+    // a new vibration starts from scratch
+    let resent = limiter
+        .admit(constant(), Duration::from_millis(1000))
+        .unwrap();
+    assert_eq!(resent, FfOutcome::Accepted(constant()));
+}
+
+#[test]
+fn overlapping_reports_are_not_double_counted() {
+    let mut limiter = FfLimiter::new(FfLimits {
+        max_on_time: Duration::from_secs(10),
+        duty_cycle: 0.05,
+        window: Duration::from_secs(60),
+        ..FfLimits::default()
+    });
+    assert_eq!(limiter.limits().budget(), Duration::from_secs(3));
+
+    limiter.admit(constant(), Duration::from_secs(0)).unwrap();
+    // replaces the first one after half a second
+    limiter
+        .admit(constant(), Duration::from_millis(500))
+        .unwrap();
+    assert_eq!(limiter.used(Duration::from_secs(2)), Duration::from_secs(2));
+
+    // one second left in the budget
+    let outcome = limiter.admit(constant(), Duration::from_secs(2)).unwrap();
+    assert_eq!(
+        outcome,
+        FfOutcome::Clamped {
+            report: FfReport::new_with_params(0xFF, 50, 0, 2),
+            adjustments: vec![Adjustment::Budget],
+        }
+    );
+}
+
+#[test]
+fn stopping_pulses_keeps_the_vibrations_done() {
+    let mut limiter = limiter();
+    // 0.5 seconds on, 0.1 seconds off
+    let pulses = FfReport::new_with_params(0xFF, 50, 10, 3);
+    limiter.admit(pulses, Duration::from_secs(0)).unwrap();
+    // in the middle of the second pause
+    limiter
+        .admit(FfReport::new(), Duration::from_millis(1150))
+        .unwrap();
+    assert_eq!(
+        limiter.used(Duration::from_millis(1150)),
+        Duration::from_secs(1)
+    );
+}
@@ -0,0 +1,50 @@
+//! Force feedback patterns with durations beyond what a single report supports.
+use std::time::Duration;
+
+use vcs_classic_hid::force_feedback::pattern::{FfPattern, FfSequencer};
+
+fn ms(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+#[test]
+fn long_pulses_are_split() {
+    let pattern = FfPattern::new().pulse(1., ms(3_000), ms(4_000), 2);
+    assert_eq!(pattern.duration(), ms(14_000));
+    // each vibration becomes two continuous segments followed by a pause
+    assert_eq!(pattern.segments().len(), 6);
+
+    let mut sequencer = FfSequencer::new(pattern);
+    let mut params = |now| {
+        sequencer
+            .poll(now)
+            .iter()
+            .map(|r| (r.intensity(), r.up_time(), r.down_time(), r.times()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(params(ms(0)), vec![(255, 255, 0, 1)]);
+    assert_eq!(params(ms(2_550)), vec![(255, 45, 0, 1)]);
+    // the gap is not described by any report
+    assert_eq!(params(ms(3_000)), vec![]);
+    assert_eq!(params(ms(6_990)), vec![]);
+    assert_eq!(params(ms(7_000)), vec![(255, 255, 0, 1)]);
+    assert_eq!(params(ms(9_550)), vec![(255, 45, 0, 1)]);
+    assert_eq!(params(ms(14_000)), vec![]);
+    assert!(sequencer.is_done());
+}
+
+#[test]
+fn short_pulses_are_kept() {
+    let pattern = FfPattern::new().pulse(0.5, ms(100), ms(2_550), 3);
+    assert_eq!(pattern.segments().len(), 1);
+    let report = pattern.segments()[0].to_report();
+    assert_eq!(
+        (
+            report.intensity(),
+            report.up_time(),
+            report.down_time(),
+            report.times()
+        ),
+        (128, 10, 255, 3)
+    );
+}
@@ -0,0 +1,170 @@
+//! Scheduling of foreground LED animations over a background.
+use vcs_classic_hid::led::{
+    anims::{Pulsate, Still},
+    scheduler::{Preemption, Scheduler},
+    AnimationEvent, LedAnimation, LedReport,
+};
+
+const BACKGROUND: u8 = 0x10;
+
+/// Shows the number of ticks since it was reset on the first LED.
+#[derive(Debug, Default)]
+struct Counter {
+    start: u64,
+}
+
+impl LedAnimation for Counter {
+    fn reset(&mut self, ticks: u64) {
+        self.start = ticks;
+    }
+
+    fn update(&mut self, ticks: u64, report: &mut LedReport) -> AnimationEvent {
+        report.set(0, (ticks - self.start) as u8);
+        AnimationEvent::Running
+    }
+}
+
+fn scheduler() -> Scheduler {
+    let mut scheduler = Scheduler::new();
+    scheduler.set_background(Still::new(LedReport::filled(BACKGROUND)));
+    scheduler
+}
+
+fn still(value: u8) -> Still {
+    Still::new(LedReport::filled(value))
+}
+
+/// The value of the first LED after an update.
+fn first_led(scheduler: &mut Scheduler, ticks: u64) -> u8 {
+    let mut report = LedReport::new();
+    assert_eq!(
+        scheduler.update(ticks, &mut report),
+        AnimationEvent::Running
+    );
+    report.get(0)
+}
+
+#[test]
+fn preempt_and_resume() {
+    let mut scheduler = scheduler();
+    let counter = scheduler
+        .enqueue(Counter::default(), 1, None, Preemption::Queue)
+        .unwrap();
+    for ticks in 0..5 {
+        assert_eq!(first_led(&mut scheduler, ticks), ticks as u8);
+    }
+
+    let flash = scheduler
+        .enqueue(still(0xFF), 2, Some(3), Preemption::Preempt)
+        .unwrap();
+    assert_eq!(first_led(&mut scheduler, 5), 0xFF);
+    assert_eq!(scheduler.active(), Some(flash));
+    assert_eq!(first_led(&mut scheduler, 7), 0xFF);
+
+    // resumes where it was interrupted
+    assert_eq!(first_led(&mut scheduler, 8), 5);
+    assert_eq!(scheduler.active(), Some(counter));
+    assert_eq!(first_led(&mut scheduler, 9), 6);
+}
+
+#[test]
+fn lower_priority_does_not_preempt() {
+    let mut scheduler = scheduler();
+    scheduler.enqueue(still(0xAA), 2, Some(4), Preemption::Queue);
+    assert_eq!(first_led(&mut scheduler, 0), 0xAA);
+    scheduler.enqueue(still(0xFF), 1, Some(4), Preemption::Preempt);
+    assert_eq!(first_led(&mut scheduler, 1), 0xAA);
+    assert_eq!(first_led(&mut scheduler, 4), 0xFF);
+}
+
+#[test]
+fn queue_and_skip() {
+    let mut scheduler = scheduler();
+    let first = scheduler
+        .enqueue(still(0xAA), 1, Some(4), Preemption::Queue)
+        .unwrap();
+    assert_eq!(first_led(&mut scheduler, 0), 0xAA);
+
+    // higher priority, but waits for its turn
+    let second = scheduler
+        .enqueue(still(0xBB), 5, Some(4), Preemption::Queue)
+        .unwrap();
+    // discarded, something is already shown
+    assert_eq!(
+        scheduler.enqueue(still(0xCC), 9, Some(4), Preemption::Skip),
+        None
+    );
+    assert_eq!(first_led(&mut scheduler, 3), 0xAA);
+    assert_eq!(scheduler.active(), Some(first));
+
+    assert_eq!(first_led(&mut scheduler, 4), 0xBB);
+    assert_eq!(scheduler.active(), Some(second));
+    assert_eq!(first_led(&mut scheduler, 8), BACKGROUND);
+    assert!(scheduler.is_idle());
+
+    // nothing shown, so not skipped
+    assert!(scheduler
+        .enqueue(still(0xCC), 0, Some(4), Preemption::Skip)
+        .is_some());
+    assert_eq!(first_led(&mut scheduler, 9), 0xCC);
+}
+
+#[test]
+fn duration_expiry() {
+    let mut scheduler = scheduler();
+    let id = scheduler
+        .enqueue(still(0xFF), 1, Some(10), Preemption::Queue)
+        .unwrap();
+    // the duration counts from the first update showing it
+    assert_eq!(first_led(&mut scheduler, 20), 0xFF);
+    assert_eq!(first_led(&mut scheduler, 29), 0xFF);
+    assert_eq!(scheduler.active(), Some(id));
+    assert_eq!(first_led(&mut scheduler, 30), BACKGROUND);
+    assert_eq!(scheduler.active(), None);
+    assert!(scheduler.is_idle());
+}
+
+#[test]
+fn reset_to_lower_ticks() {
+    let mut scheduler = scheduler();
+    scheduler.enqueue(Pulsate::new(), 1, Some(100), Preemption::Queue);
+    let mut report = LedReport::new();
+    scheduler.update(500, &mut report);
+    scheduler.reset(0);
+    scheduler.update(0, &mut report);
+    // starts over with its full duration
+    assert!(!scheduler.is_idle());
+    scheduler.update(99, &mut report);
+    assert!(!scheduler.is_idle());
+    scheduler.update(100, &mut report);
+    assert!(scheduler.is_idle());
+}
+
+#[test]
+fn reset_rewinds_interrupted_animations() {
+    let mut scheduler = scheduler();
+    scheduler.enqueue(Counter::default(), 1, None, Preemption::Queue);
+    assert_eq!(first_led(&mut scheduler, 100), 0);
+    assert_eq!(first_led(&mut scheduler, 150), 50);
+    scheduler.enqueue(still(0xFF), 2, Some(10), Preemption::Preempt);
+    assert_eq!(first_led(&mut scheduler, 200), 0xFF);
+
+    scheduler.reset(0);
+    assert_eq!(first_led(&mut scheduler, 0), 0xFF);
+    // the interrupted counter starts over
+    assert_eq!(first_led(&mut scheduler, 10), 0);
+    assert_eq!(first_led(&mut scheduler, 12), 2);
+}
+
+#[test]
+fn ticks_going_backwards() {
+    let mut scheduler = scheduler();
+    scheduler.enqueue(Pulsate::new(), 1, Some(100), Preemption::Queue);
+    scheduler.enqueue(still(0xFF), 2, Some(10), Preemption::Preempt);
+    let mut report = LedReport::new();
+    scheduler.update(500, &mut report);
+    // without a reset, nothing panics
+    scheduler.update(0, &mut report);
+    scheduler.update(20, &mut report);
+    assert!(!scheduler.is_idle());
+}
@@ -0,0 +1,46 @@
+//! Transitions between LED animations over time.
+use vcs_classic_hid::led::{
+    anims::{Asr, Still},
+    transition::{Transition, TransitionStyle},
+    AnimationEvent, LedAnimation, LedReport, LedSelection,
+};
+
+fn transition<B: LedAnimation>(incoming: B) -> Transition<Still, B> {
+    Transition::new(
+        Still::new(LedReport::filled(0xFF)),
+        incoming,
+        TransitionStyle::Crossfade,
+        10,
+    )
+}
+
+#[test]
+fn update_before_reset_tick() {
+    let mut transition = transition(Still::new(LedReport::new()));
+    transition.reset(100);
+    let mut report = LedReport::new();
+    assert_eq!(transition.update(50, &mut report), AnimationEvent::Running);
+    // not started yet, only the outgoing animation is shown
+    assert_eq!(report.get(0), 0xFF);
+}
+
+#[test]
+fn incoming_ends_during_transition() {
+    // lit for 2 ticks, then ended
+    let mut transition = transition(Asr::new_with_params(LedSelection::ALL, 0xFF, 0, 2, 0));
+    transition.reset(0);
+    let mut report = LedReport::new();
+    for ticks in 0..10 {
+        assert_eq!(
+            transition.update(ticks, &mut report),
+            AnimationEvent::Running,
+            "tick {}",
+            ticks
+        );
+    }
+    // blending into the last frame of the incoming animation
+    assert!(report.get(0) < 0x20);
+
+    assert_eq!(transition.update(10, &mut report), AnimationEvent::Ended);
+    assert_eq!(report.ring(), [0; 24]);
+}